@@ -2,9 +2,15 @@ use bevy::prelude::*;
 use bevy_prototype_debug_lines::DebugLines;
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
 use std::f32::consts::PI;
 
+use crate::atmosphere::{Atmosphere, GRAVITY};
+use crate::controls::{FlapLever, Trim};
 use crate::input::{AirplaneAction, AirplaneControls};
+use crate::soaring::SoaringWeather;
+use crate::turbulence::Turbulence;
+use crate::wind::Wind;
 
 pub struct AerodynamicsPlugin;
 
@@ -22,22 +28,36 @@ impl Plugin for AerodynamicsPlugin {
     }
 }
 
-fn update_control_surface_angle(
+pub fn update_control_surface_angle(
     input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
-    mut surface_list_query: Query<&mut AeroSurfaceList>,
+    mut surface_list_query: Query<(&mut AeroSurfaceList, Option<&FlapLever>, Option<&Trim>)>,
+    time: Res<Time>,
 ) {
     let action_state = input_query.single();
 
-    for mut surface_list in &mut surface_list_query {
+    for (mut surface_list, flap_lever, trim) in &mut surface_list_query {
+        let flap_angle = flap_lever.map_or(0.0, |flap_lever| flap_lever.angle);
+        let pitch_input = action_state.clamped_value(AirplaneAction::Pitch);
+        let yaw_input = action_state.clamped_value(AirplaneAction::Yaw);
+        let (pitch_input, yaw_input) = match trim {
+            Some(trim) => (trim.pitch_input(pitch_input), trim.yaw_input(yaw_input)),
+            None => (pitch_input, yaw_input),
+        };
+        // The airbrake lever is pulled back to extend the airbrakes.
+        let airbrake_deployment = (-action_state.clamped_value(AirplaneAction::Airbrake)).max(0.0);
         for (surface, _) in &mut surface_list.surfaces {
-            let input_value = match surface.input_type {
+            let commanded_angle = match surface.input_type {
                 ControlInputType::None => 0.0,
-                ControlInputType::Roll => action_state.clamped_value(AirplaneAction::Roll),
-                ControlInputType::Pitch => action_state.clamped_value(AirplaneAction::Pitch),
-                ControlInputType::Yaw => action_state.clamped_value(AirplaneAction::Yaw),
-                ControlInputType::Flap => 0.0,
+                ControlInputType::Roll => {
+                    surface.input_sensitivity * action_state.clamped_value(AirplaneAction::Roll)
+                }
+                ControlInputType::Pitch => surface.input_sensitivity * pitch_input,
+                ControlInputType::Yaw => surface.input_sensitivity * yaw_input,
+                // The detent angles are the flap deflections, trailing edge down.
+                ControlInputType::Flap => -flap_angle,
             };
-            surface.control_surface_angle = surface.input_sensitivity * input_value;
+            surface.actuate(commanded_angle, time.delta_seconds());
+            surface.airbrake_deployment = airbrake_deployment;
         }
     }
 }
@@ -57,51 +77,75 @@ fn draw_debug_visualizations(
         );
         for (surface, transform) in &surface_list.surfaces {
             let surface_transform = global_transform.mul_transform(*transform);
-            let back = surface_transform.back();
             let right = surface_transform.right();
             let up = surface_transform.up();
-            let half_chord = surface.config.chord * 0.5;
             let half_span = surface.config.span * 0.5;
-            let leading_edge = surface_transform.translation() - back * half_chord;
-            let hinge = leading_edge
-                + back * surface.config.chord * (1.0 - surface.config.control_surface_fraction);
-            let trailing_edge = hinge
-                + (up * surface.control_surface_angle.sin()
-                    + back * surface.control_surface_angle.cos())
-                    * surface.config.chord
-                    * surface.config.control_surface_fraction;
-
-            let p0 = leading_edge - right * half_span;
-            let p1 = leading_edge + right * half_span;
-            let p2 = hinge - right * half_span;
-            let p3 = hinge + right * half_span;
-            let p4 = trailing_edge - right * half_span;
-            let p5 = trailing_edge + right * half_span;
-            lines.line_colored(p0, p1, 0.0, Color::BLUE);
-            lines.line_colored(p2, p3, 0.0, Color::BLUE);
-            lines.line_colored(p4, p5, 0.0, Color::RED);
-            lines.line_colored(p0, p2, 0.0, Color::BLUE);
-            lines.line_colored(p1, p3, 0.0, Color::BLUE);
-            lines.line_colored(p2, p4, 0.0, Color::RED);
-            lines.line_colored(p3, p5, 0.0, Color::RED);
-
-            lines.line_colored(
-                surface_transform.translation(),
-                surface_transform.translation() + surface.lift * 0.001,
-                0.0,
-                Color::GREEN,
-            );
-            lines.line_colored(
-                surface_transform.translation(),
-                surface_transform.translation() + surface.drag * 0.001,
-                0.0,
-                Color::PINK,
-            );
+
+            // Outline of each strip, with its own chord and twist.
+            let half_strip_width = half_span / surface.config.strip_count.max(1) as f32;
+            for (strip_transform, chord) in surface.strip_geometry() {
+                let strip_transform = surface_transform.mul_transform(strip_transform);
+                let back = strip_transform.back();
+                let right = strip_transform.right();
+                let up = strip_transform.up();
+                let leading_edge = strip_transform.translation() - back * chord * 0.5;
+                let hinge =
+                    leading_edge + back * chord * (1.0 - surface.config.control_surface_fraction);
+                let trailing_edge = hinge
+                    + (up * surface.control_surface_angle.sin()
+                        + back * surface.control_surface_angle.cos())
+                        * chord
+                        * surface.config.control_surface_fraction;
+
+                let p0 = leading_edge - right * half_strip_width;
+                let p1 = leading_edge + right * half_strip_width;
+                let p2 = hinge - right * half_strip_width;
+                let p3 = hinge + right * half_strip_width;
+                let p4 = trailing_edge - right * half_strip_width;
+                let p5 = trailing_edge + right * half_strip_width;
+                lines.line_colored(p0, p1, 0.0, Color::BLUE);
+                lines.line_colored(p2, p3, 0.0, Color::BLUE);
+                lines.line_colored(p4, p5, 0.0, Color::RED);
+                lines.line_colored(p0, p2, 0.0, Color::BLUE);
+                lines.line_colored(p1, p3, 0.0, Color::BLUE);
+                lines.line_colored(p2, p4, 0.0, Color::RED);
+                lines.line_colored(p3, p5, 0.0, Color::RED);
+            }
+
+            if surface.config.airbrake_span_fraction > 0.0 && surface.airbrake_deployment > 0.0 {
+                let half_airbrake_span = half_span * surface.config.airbrake_span_fraction;
+                let airbrake_base = surface_transform.translation();
+                let airbrake_top =
+                    airbrake_base + up * surface.config.chord * 0.15 * surface.airbrake_deployment;
+                let q0 = airbrake_base - right * half_airbrake_span;
+                let q1 = airbrake_base + right * half_airbrake_span;
+                let q2 = airbrake_top - right * half_airbrake_span;
+                let q3 = airbrake_top + right * half_airbrake_span;
+                lines.line_colored(q0, q2, 0.0, Color::YELLOW);
+                lines.line_colored(q1, q3, 0.0, Color::YELLOW);
+                lines.line_colored(q2, q3, 0.0, Color::YELLOW);
+            }
+
+            for strip in &surface.strips {
+                let strip_position = surface_transform.transform_point(strip.position);
+                lines.line_colored(
+                    strip_position,
+                    strip_position + strip.lift * 0.001,
+                    0.0,
+                    Color::GREEN,
+                );
+                lines.line_colored(
+                    strip_position,
+                    strip_position + strip.drag * 0.001,
+                    0.0,
+                    Color::PINK,
+                );
+            }
         }
     }
 }
 
-#[derive(Reflect, FromReflect, Default, Clone, Copy)]
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
 pub enum ControlInputType {
     #[default]
     None,
@@ -111,7 +155,8 @@ pub enum ControlInputType {
     Flap,
 }
 
-#[derive(Reflect, FromReflect, Clone, Copy)]
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+#[serde(default)]
 pub struct AeroSurfaceConfig {
     pub lift_slope: f32,
     pub skin_friction: f32,
@@ -121,6 +166,26 @@ pub struct AeroSurfaceConfig {
     pub chord: f32,
     pub span: f32,
     pub control_surface_fraction: f32,
+    pub airbrake_span_fraction: f32,
+    pub airbrake_drag_coefficient: f32,
+    // Ratio of the local to the free stream dynamic pressure, e.g. the tail efficiency of a
+    // surface in the wake of the wing.
+    pub dynamic_pressure_ratio: f32,
+    // Number of spanwise strips the surface is split into. Each strip is evaluated at its own
+    // position, with its own chord and twist.
+    pub strip_count: usize,
+    pub span_root: SpanRoot,
+    pub taper_ratio: f32, // tip chord / root chord
+    pub washout: f32,     // radians, nose down twist of the tip relative to the root
+}
+
+// Side of the surface (along its local x axis) at which the root chord is located.
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
+pub enum SpanRoot {
+    #[default]
+    Center,
+    NegativeX,
+    PositiveX,
 }
 
 impl Default for AeroSurfaceConfig {
@@ -134,24 +199,155 @@ impl Default for AeroSurfaceConfig {
             chord: 1.0,
             span: 2.0,
             control_surface_fraction: 0.0,
+            airbrake_span_fraction: 0.0,
+            airbrake_drag_coefficient: 0.0,
+            dynamic_pressure_ratio: 1.0,
+            strip_count: 1,
+            span_root: SpanRoot::Center,
+            taper_ratio: 1.0,
+            washout: 0.0,
         }
     }
 }
 
+impl AeroSurfaceConfig {
+    // Rejects geometry that can't be simulated, e.g. a span of zero produces NaN forces.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.span <= 0.0 || self.chord <= 0.0 {
+            Err("span and chord must be positive")
+        } else if self.strip_count == 0 {
+            Err("strip_count must be at least 1")
+        } else if self.taper_ratio <= 0.0 {
+            Err("taper_ratio must be positive")
+        } else if !(0.0..=1.0).contains(&self.control_surface_fraction) {
+            Err("control_surface_fraction must be between 0 and 1")
+        } else if !(0.0..=1.0).contains(&self.airbrake_span_fraction) {
+            Err("airbrake_span_fraction must be between 0 and 1")
+        } else if self.stall_angle_low >= self.stall_angle_high {
+            Err("stall_angle_low must be below stall_angle_high")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Actuator moving a control surface towards the commanded angle. Positive angles deflect the
+// trailing edge up.
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct ActuatorConfig {
+    pub max_rate: f32,      // radians per second
+    pub time_constant: f32, // seconds, first-order lag
+    pub max_angle: f32,     // radians, trailing edge up
+    pub min_angle: f32,     // radians, trailing edge down
+    // Dynamic pressure at which the aerodynamic hinge moment halves the reachable deflection.
+    pub blowback_dynamic_pressure: f32, // Pa
+}
+
+impl Default for ActuatorConfig {
+    fn default() -> Self {
+        Self {
+            max_rate: 2.0,       // radians per second (= 115 degrees per second)
+            time_constant: 0.05, // seconds
+            max_angle: 0.79,     // radians (= 45 degrees)
+            min_angle: -0.79,    // radians (= -45 degrees)
+            blowback_dynamic_pressure: f32::INFINITY,
+        }
+    }
+}
+
+// Aerodynamic forces and torque of a surface, in the surface's local frame.
+#[derive(Default, Clone, Copy)]
+pub struct SurfaceForces {
+    pub lift: Vec3,
+    pub drag: Vec3,
+    pub torque: Vec3,
+    pub lift_coefficient: f32,
+}
+
 #[derive(Reflect, FromReflect, Default, Clone, Copy)]
+pub struct SurfaceStrip {
+    pub position: Vec3, // in the surface's local frame
+    pub lift: Vec3,
+    pub drag: Vec3,
+}
+
+#[derive(Reflect, FromReflect, Default, Clone)]
 pub struct AeroSurface {
+    pub name: String,
     pub config: AeroSurfaceConfig,
     pub input_type: ControlInputType,
     pub input_sensitivity: f32,
+    pub actuator: ActuatorConfig,
     pub control_surface_angle: f32,
+    pub dynamic_pressure: f32,
+    pub airbrake_deployment: f32,
+    pub lift_coefficient: f32,
     pub lift: Vec3,
     pub drag: Vec3,
     pub torque: Vec3,
+    pub strips: Vec<SurfaceStrip>,
 }
 
 impl AeroSurface {
-    pub fn calculate_forces(&self, mut local_air_velocity: Vec3, air_density: f32) -> Vec3 {
-        let aspect_ratio = self.config.span / self.config.chord;
+    pub fn actuate(&mut self, commanded_angle: f32, delta_seconds: f32) {
+        let blowback_factor =
+            1.0 / (1.0 + self.dynamic_pressure / self.actuator.blowback_dynamic_pressure);
+        let target_angle = commanded_angle.clamp(
+            self.actuator.min_angle * blowback_factor,
+            self.actuator.max_angle * blowback_factor,
+        );
+
+        let lag_factor = if self.actuator.time_constant > 0.0 {
+            1.0 - (-delta_seconds / self.actuator.time_constant).exp()
+        } else {
+            1.0
+        };
+        let max_step = self.actuator.max_rate * delta_seconds;
+        self.control_surface_angle +=
+            ((target_angle - self.control_surface_angle) * lag_factor).clamp(-max_step, max_step);
+    }
+
+    // Local transforms (relative to the surface) and chords of the spanwise strips. The chord
+    // is scaled so that the mean chord of the tapered surface equals `config.chord`.
+    pub fn strip_geometry(&self) -> Vec<(Transform, f32)> {
+        let strip_count = self.config.strip_count.max(1);
+        let strip_width = self.config.span / strip_count as f32;
+        let half_span = self.config.span * 0.5;
+        let taper_ratio = self.config.taper_ratio;
+
+        (0..strip_count)
+            .map(|index| {
+                let x = -half_span + (index as f32 + 0.5) * strip_width;
+                // Relative spanwise position, 0 at the root and 1 at the tip.
+                let eta = match self.config.span_root {
+                    SpanRoot::Center => x.abs() / half_span,
+                    SpanRoot::NegativeX => (x + half_span) / self.config.span,
+                    SpanRoot::PositiveX => (half_span - x) / self.config.span,
+                };
+                let chord = self.config.chord * (1.0 + (taper_ratio - 1.0) * eta) * 2.0
+                    / (1.0 + taper_ratio);
+                let transform = Transform::from_xyz(x, 0.0, 0.0)
+                    .with_rotation(Quat::from_rotation_x(-self.config.washout * eta));
+                (transform, chord)
+            })
+            .collect()
+    }
+
+    pub fn calculate_forces(
+        &self,
+        local_air_velocity: Vec3,
+        air_density: f32,
+        chord: f32,
+        span: f32,
+    ) -> SurfaceForces {
+        // A surface with its root at one side is half of a wing that is mirrored at the root,
+        // e.g. the left wing of a glider. Its induced flow depends on the span of the whole
+        // wing.
+        let aspect_ratio = match self.config.span_root {
+            SpanRoot::Center => self.config.span / self.config.chord,
+            SpanRoot::NegativeX | SpanRoot::PositiveX => 2.0 * self.config.span / self.config.chord,
+        };
 
         // Accounting for aspect ratio effect on lift coefficient.
         let corrected_lift_slope = self.config.lift_slope * aspect_ratio
@@ -180,11 +376,19 @@ impl AeroSurface {
         let stall_angle_high = zero_lift_aoa + cl_max_high / corrected_lift_slope;
         let stall_angle_low = zero_lift_aoa + cl_max_low / corrected_lift_slope;
 
-        local_air_velocity.x = 0.0;
+        // Sideslip or sweep splits the flow into a chordwise and a spanwise component. Only the
+        // chordwise component produces lift and pressure drag (cosine law), the spanwise
+        // component only adds skin friction. The angle of attack is measured in the surface's
+        // own frame, so that sideslip changes the angle of attack of panels with dihedral and
+        // produces a rolling moment (dihedral effect).
+        let chordwise_air_velocity = Vec3::new(0.0, local_air_velocity.y, local_air_velocity.z);
+        let spanwise_air_velocity = local_air_velocity.x;
 
-        let area = self.config.chord * self.config.span;
+        let area = chord * span;
 
-        let dynamic_pressure = 0.5 * air_density * local_air_velocity.length_squared();
+        let dynamic_pressure = 0.5 * air_density * chordwise_air_velocity.length_squared();
+        let spanwise_dynamic_pressure =
+            0.5 * air_density * spanwise_air_velocity * spanwise_air_velocity.abs();
         let angle_of_attack = (-local_air_velocity.y).atan2(local_air_velocity.z);
 
         let aerodynamic_coefficients = self.calculate_coefficients(
@@ -196,11 +400,28 @@ impl AeroSurface {
             stall_angle_low,
         );
 
-        let lift = aerodynamic_coefficients.x * dynamic_pressure * area;
-        let drag = aerodynamic_coefficients.y * dynamic_pressure * area;
-        let torque = aerodynamic_coefficients.z * dynamic_pressure * area * self.config.chord;
+        // Extended airbrakes destroy the lift on the part of the span they cover and add drag.
+        let airbrake_lift_factor =
+            1.0 - self.config.airbrake_span_fraction * self.airbrake_deployment;
+        let airbrake_drag_coefficient =
+            self.config.airbrake_drag_coefficient * self.airbrake_deployment;
+
+        let lift_coefficient = aerodynamic_coefficients.x * airbrake_lift_factor;
+        let lift = lift_coefficient * dynamic_pressure * area;
+        let drag =
+            (aerodynamic_coefficients.y + airbrake_drag_coefficient) * dynamic_pressure * area;
+        let torque = aerodynamic_coefficients.z * dynamic_pressure * area * chord;
+        let spanwise_drag = self.config.skin_friction * spanwise_dynamic_pressure * area;
+
+        let drag_direction = chordwise_air_velocity.normalize_or_zero();
+        let lift_direction = drag_direction.cross(Vec3::NEG_X);
 
-        Vec3::new(lift, drag, torque)
+        SurfaceForces {
+            lift: lift * lift_direction,
+            drag: drag * drag_direction + spanwise_drag * Vec3::X,
+            torque: torque * Vec3::Z,
+            lift_coefficient,
+        }
     }
 
     fn calculate_coefficients(
@@ -307,8 +528,9 @@ impl AeroSurface {
 
         let normal_coefficient = (lift_coefficent + effective_angle.sin() * tangential_coefficient)
             / effective_angle.cos();
-        let drag_coefficient = normal_coefficient * effective_angle.sin()
-            + tangential_coefficient * effective_angle.cos();
+        // In attached flow the suction at the leading edge cancels the drag of the normal
+        // force, leaving the induced drag and the skin friction.
+        let drag_coefficient = lift_coefficent * induced_angle + self.config.skin_friction;
         let torque_coefficient =
             -normal_coefficient * Self::torque_coefficient_proportion(effective_angle);
 
@@ -382,86 +604,605 @@ fn lerp_clamped(a: f32, b: f32, mut t: f32) -> f32 {
     return a + t * (b - a);
 }
 
-#[derive(Reflect, Default, Component)]
+// State of the air mass the airplane is flying through.
+pub struct AmbientAir<'a> {
+    pub atmosphere: &'a Atmosphere,
+    pub wind: &'a Wind,
+    pub soaring_weather: &'a SoaringWeather,
+    pub time: f32,
+}
+
+impl AmbientAir<'_> {
+    pub fn wind_velocity(&self, position: Vec3) -> Vec3 {
+        let wind_velocity = self.wind.wind_velocity(position, self.time);
+        wind_velocity
+            + Vec3::Y
+                * self
+                    .soaring_weather
+                    .vertical_velocity(position, wind_velocity)
+    }
+
+    pub fn density(&self, position: Vec3) -> f32 {
+        self.atmosphere.density(position.y)
+    }
+}
+
+// Declares that a surface sits in the wake of another surface of the same list, e.g. the
+// horizontal stabilizer behind the wing. The upstream surface deflects the flow downwards by
+// an angle proportional to its lift coefficient.
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+pub struct Wake {
+    pub upstream_surface: usize,
+    pub downstream_surface: usize,
+    pub downwash_gradient: f32, // radians per unit lift coefficient, 2 / (pi * aspect ratio)
+}
+
+impl Wake {
+    pub fn validate(&self, surface_count: usize) -> Result<(), String> {
+        if self.upstream_surface >= surface_count || self.downstream_surface >= surface_count {
+            Err(format!(
+                "surface index out of range, there are {} surfaces",
+                surface_count
+            ))
+        } else if self.upstream_surface == self.downstream_surface {
+            Err("a surface can't be in its own wake".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Reflect, Default, Component, Clone)]
 pub struct AeroSurfaceList {
     pub surfaces: Vec<(AeroSurface, Transform)>,
+    pub wakes: Vec<Wake>,
+    pub aerodynamic_force: Vec3, // N, sum of all surfaces
+    // Aerodynamic force along the airplane's vertical axis per weight, as felt by the pilot.
+    pub load_factor: f32,
 }
 
 impl AeroSurfaceList {
+    pub fn surface(&self, name: &str) -> Option<&AeroSurface> {
+        self.surfaces
+            .iter()
+            .map(|(surface, _)| surface)
+            .find(|surface| surface.name == name)
+    }
+
     pub fn calculate_forces(
         &mut self,
         external_force: &mut ExternalForce,
-        world_center_of_mass: Vec3,
+        mass_properties: &MassProperties,
         plane_transform: &Transform,
         velocity: &Velocity,
+        ambient_air: &AmbientAir,
+        turbulence: Option<&Turbulence>,
     ) {
-        for (surface, surface_transform) in &mut self.surfaces {
+        let world_center_of_mass =
+            plane_transform.transform_point(mass_properties.local_center_of_mass);
+        self.aerodynamic_force = Vec3::ZERO;
+
+        for index in 0..self.surfaces.len() {
+            // Upstream surfaces evaluated earlier in this loop contribute their current lift
+            // coefficient, the others the one of the previous update. Lift coefficients are
+            // negative for upward lift, which has to produce a downwash (positive angle).
+            let downwash_angle: f32 = self
+                .wakes
+                .iter()
+                .filter(|wake| wake.downstream_surface == index)
+                .filter_map(|wake| {
+                    self.surfaces
+                        .get(wake.upstream_surface)
+                        .map(|(upstream, _)| -wake.downwash_gradient * upstream.lift_coefficient)
+                })
+                .sum();
+
+            let (surface, surface_transform) = &mut self.surfaces[index];
             let surface_plane_transform = plane_transform.mul_transform(*surface_transform);
+            let strip_width = surface.config.span / surface.config.strip_count.max(1) as f32;
 
-            let world_position = surface_plane_transform.translation;
-            let relative_position = world_position - world_center_of_mass;
+            let mut surface_lift = Vec3::ZERO;
+            let mut surface_drag = Vec3::ZERO;
+            let mut surface_torque = Vec3::ZERO;
+            let mut lift_coefficient = 0.0;
+            let mut dynamic_pressure = 0.0;
+            let mut total_area = 0.0;
+            surface.strips.clear();
 
-            let air_velocity = -velocity.linvel - velocity.angvel.cross(relative_position);
-            let local_air_velocity = surface_plane_transform
-                .rotation
-                .conjugate()
-                .mul_vec3(air_velocity);
+            for (strip_transform, strip_chord) in surface.strip_geometry() {
+                let strip_plane_transform = surface_plane_transform.mul_transform(strip_transform);
 
-            let (surface_lift, surface_drag, surface_torque) =
-                surface.calculate_forces(local_air_velocity, 1.2).into();
+                let world_position = strip_plane_transform.translation;
+                let relative_position = world_position - world_center_of_mass;
 
-            let mut drag_direction = air_velocity.normalize();
-            if drag_direction.is_nan() {
-                drag_direction = Vec3::ZERO;
-            }
-            let mut lift_direction = drag_direction.cross(surface_plane_transform.left());
-            if lift_direction.is_nan() {
-                lift_direction = Vec3::ZERO;
+                let mut air_velocity = ambient_air.wind_velocity(world_position)
+                    - velocity.linvel
+                    - velocity.angvel.cross(relative_position);
+                if let Some(turbulence) = turbulence {
+                    let local_relative_position = plane_transform
+                        .rotation
+                        .conjugate()
+                        .mul_vec3(relative_position);
+                    air_velocity += plane_transform
+                        .rotation
+                        .mul_vec3(turbulence.gust_velocity(local_relative_position));
+                }
+                if downwash_angle != 0.0 {
+                    // Tilt the flow downwards about the airplane's lateral axis.
+                    air_velocity = Quat::from_axis_angle(plane_transform.right(), downwash_angle)
+                        .mul_vec3(air_velocity);
+                }
+                air_velocity *= surface.config.dynamic_pressure_ratio.sqrt();
+                let local_air_velocity = strip_plane_transform
+                    .rotation
+                    .conjugate()
+                    .mul_vec3(air_velocity);
+
+                let air_density = ambient_air.density(world_position);
+
+                let strip_forces = surface.calculate_forces(
+                    local_air_velocity,
+                    air_density,
+                    strip_chord,
+                    strip_width,
+                );
+
+                let lift = strip_plane_transform.rotation * strip_forces.lift;
+                let drag = strip_plane_transform.rotation * strip_forces.drag;
+                let torque = strip_plane_transform.rotation * strip_forces.torque;
+
+                let total_force = lift + drag;
+
+                external_force.force += total_force;
+                external_force.torque += relative_position.cross(total_force);
+                external_force.torque += torque;
+
+                let area = strip_chord * strip_width;
+                surface_lift += lift;
+                surface_drag += drag;
+                surface_torque += torque;
+                lift_coefficient += strip_forces.lift_coefficient * area;
+                dynamic_pressure += 0.5 * air_density * air_velocity.length_squared() * area;
+                total_area += area;
+
+                // for visualizations
+                surface.strips.push(SurfaceStrip {
+                    position: strip_transform.translation,
+                    lift,
+                    drag,
+                });
             }
 
-            let lift = surface_lift * lift_direction;
-            let drag = surface_drag * drag_direction;
-            let torque = surface_torque * surface_plane_transform.back();
+            surface.lift_coefficient = lift_coefficient / total_area;
+            surface.dynamic_pressure = dynamic_pressure / total_area;
 
             // for visualizations
-            surface.lift = lift;
-            surface.drag = drag;
-            surface.torque = torque;
+            surface.lift = surface_lift;
+            surface.drag = surface_drag;
+            surface.torque = surface_torque;
 
-            let total_force = lift + drag;
-
-            external_force.force += total_force;
-            external_force.torque += relative_position.cross(total_force);
-            external_force.torque += torque;
+            self.aerodynamic_force += surface_lift + surface_drag;
         }
 
-        // external_force.force += plane_transform.forward() * 10000.0;
+        let mass = mass_properties.mass;
+        self.load_factor = if mass > 0.0 {
+            self.aerodynamic_force.dot(plane_transform.up()) / (mass * GRAVITY)
+        } else {
+            0.0
+        };
+    }
+
+    // Removes a surface, e.g. a wing that broke off, together with the wakes it is part of.
+    pub fn detach_surface(&mut self, index: usize) -> (AeroSurface, Transform) {
+        self.wakes
+            .retain(|wake| wake.upstream_surface != index && wake.downstream_surface != index);
+        for wake in &mut self.wakes {
+            if wake.upstream_surface > index {
+                wake.upstream_surface -= 1;
+            }
+            if wake.downstream_surface > index {
+                wake.downstream_surface -= 1;
+            }
+        }
+        self.surfaces.remove(index)
     }
 }
 
-fn simulate_aerodynamics(
+pub fn simulate_aerodynamics(
     mut airplane_query: Query<(
         &mut AeroSurfaceList,
         &mut ExternalForce,
         &ReadMassProperties,
         &Transform,
         &Velocity,
+        Option<&Turbulence>,
     )>,
+    atmosphere: Res<Atmosphere>,
+    wind: Res<Wind>,
+    soaring_weather: Res<SoaringWeather>,
+    time: Res<Time>,
 ) {
-    for (mut surface_list, mut external_force, read_mass_properties, transform, velocity) in
-        airplane_query.iter_mut()
-    {
-        let world_center_of_mass =
-            transform.transform_point(read_mass_properties.0.local_center_of_mass);
+    let ambient_air = AmbientAir {
+        atmosphere: &atmosphere,
+        wind: &wind,
+        soaring_weather: &soaring_weather,
+        time: time.elapsed_seconds(),
+    };
 
+    for (
+        mut surface_list,
+        mut external_force,
+        read_mass_properties,
+        transform,
+        velocity,
+        turbulence,
+    ) in airplane_query.iter_mut()
+    {
         external_force.force = Vec3::ZERO;
         external_force.torque = Vec3::ZERO;
 
         surface_list.calculate_forces(
             &mut external_force,
-            world_center_of_mass,
+            &read_mass_properties.0,
             &transform,
             &velocity,
+            &ambient_air,
+            turbulence,
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::AircraftDefinition;
+
+    fn wing(config: AeroSurfaceConfig) -> AeroSurface {
+        AeroSurface {
+            config: AeroSurfaceConfig {
+                span: 10.0,
+                chord: 1.0,
+                ..config
+            },
+            ..default()
+        }
+    }
+
+    // Forces on the surfaces of a list flying through calm standard air at sea level.
+    fn list_forces(surface_list: &mut AeroSurfaceList, velocity: Velocity) -> ExternalForce {
+        list_forces_about(surface_list, Vec3::ZERO, velocity)
+    }
+
+    // The same, with the torque about a center of mass.
+    fn list_forces_about(
+        surface_list: &mut AeroSurfaceList,
+        center_of_mass: Vec3,
+        velocity: Velocity,
+    ) -> ExternalForce {
+        let atmosphere = Atmosphere::default();
+        let wind = Wind::default();
+        let soaring_weather = SoaringWeather::default();
+        let ambient_air = AmbientAir {
+            atmosphere: &atmosphere,
+            wind: &wind,
+            soaring_weather: &soaring_weather,
+            time: 0.0,
+        };
+        let mut external_force = ExternalForce::default();
+        // Without a mass the load factor stays zero, the tests only look at the forces.
+        let mass_properties = MassProperties {
+            local_center_of_mass: center_of_mass,
+            ..default()
+        };
+        surface_list.calculate_forces(
+            &mut external_force,
+            &mass_properties,
+            &Transform::IDENTITY,
+            &velocity,
+            &ambient_air,
+            None,
+        );
+        external_force
+    }
+
+    // The ASK 21 with two pilots, as described by its aircraft definition.
+    fn ask21() -> (AeroSurfaceList, MassProperties) {
+        let definition: AircraftDefinition =
+            ron::from_str(include_str!("../assets/planes/ask21/ask21.aircraft")).unwrap();
+        let mass_properties = definition.mass_balance().mass_properties(None, None);
+        (definition.aero_surface_list(), mass_properties)
+    }
+
+    // Finds the root of a monotonic function between two bounds by bisection.
+    fn bisect(mut low: f32, mut high: f32, f: impl Fn(f32) -> f32) -> f32 {
+        let sign_low = f(low).signum();
+        for _ in 0..40 {
+            let middle = 0.5 * (low + high);
+            if f(middle).signum() == sign_low {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        0.5 * (low + high)
+    }
+
+    struct Glide {
+        lift: f32,     // N
+        drag: f32,     // N
+        elevator: f32, // radians
+    }
+
+    impl Glide {
+        fn glide_ratio(&self) -> f32 {
+            self.lift / self.drag
+        }
+    }
+
+    // Lift and drag of an airplane flying with an angle of attack (of its longitudinal axis)
+    // and elevator angle through calm standard air at sea level, and the pitching moment
+    // about its center of mass.
+    fn glide_forces(
+        surface_list: &AeroSurfaceList,
+        center_of_mass: Vec3,
+        airspeed: f32,
+        angle_of_attack: f32,
+        elevator: f32,
+    ) -> (Glide, f32) {
+        let mut surface_list = surface_list.clone();
+        for (surface, _) in &mut surface_list.surfaces {
+            if let ControlInputType::Pitch = surface.input_type {
+                surface.control_surface_angle = elevator;
+            }
+        }
+        let flight_direction = Vec3::new(0.0, -angle_of_attack.sin(), -angle_of_attack.cos());
+        let velocity = Velocity::linear(flight_direction * airspeed);
+        // The downwash at the tail depends on the lift of the wings in the previous update.
+        list_forces_about(&mut surface_list, center_of_mass, velocity);
+        let external_force = list_forces_about(&mut surface_list, center_of_mass, velocity);
+
+        let drag = -external_force.force.dot(flight_direction);
+        let lift = (external_force.force + drag * flight_direction).y;
+        let glide = Glide {
+            lift,
+            drag,
+            elevator,
+        };
+        (glide, external_force.torque.x)
+    }
+
+    // Steady glide at an airspeed, trimmed with the elevator. The lift and drag together
+    // carry the weight.
+    fn trimmed_glide(
+        surface_list: &AeroSurfaceList,
+        mass_properties: &MassProperties,
+        airspeed: f32,
+    ) -> Glide {
+        let center_of_mass = mass_properties.local_center_of_mass;
+        let weight = mass_properties.mass * GRAVITY;
+        let trimmed_glide_forces = |angle_of_attack: f32| {
+            let elevator = bisect(-0.5, 0.5, |elevator| {
+                glide_forces(
+                    surface_list,
+                    center_of_mass,
+                    airspeed,
+                    angle_of_attack,
+                    elevator,
+                )
+                .1
+            });
+            glide_forces(
+                surface_list,
+                center_of_mass,
+                airspeed,
+                angle_of_attack,
+                elevator,
+            )
+            .0
+        };
+        let angle_of_attack = bisect(-0.1, 0.3, |angle_of_attack| {
+            let glide = trimmed_glide_forces(angle_of_attack);
+            glide.lift - (weight * weight - glide.drag * glide.drag).max(0.0).sqrt()
+        });
+        trimmed_glide_forces(angle_of_attack)
+    }
+
+    #[test]
+    fn swept_flow_follows_cosine_law() {
+        let surface = wing(AeroSurfaceConfig {
+            zero_lift_aoa: -0.05,
+            ..default()
+        });
+        let speed = 30.0;
+        let sweep: f32 = 0.5;
+
+        let straight = surface.calculate_forces(Vec3::new(0.0, 0.0, speed), 1.225, 1.0, 10.0);
+        let swept = surface.calculate_forces(
+            Vec3::new(speed * sweep.sin(), 0.0, speed * sweep.cos()),
+            1.225,
+            1.0,
+            10.0,
+        );
+
+        let expected = straight.lift.length() * sweep.cos().powi(2);
+        assert!((swept.lift.length() - expected).abs() < 1e-3 * expected);
+        assert!(swept.lift.x.abs() < 1e-6);
+        assert_eq!(swept.lift_coefficient, straight.lift_coefficient);
+    }
+
+    #[test]
+    fn spanwise_friction_acts_along_spanwise_flow() {
+        let surface = wing(AeroSurfaceConfig::default());
+
+        let outboard = surface.calculate_forces(Vec3::new(5.0, 0.0, 30.0), 1.225, 1.0, 10.0);
+        let inboard = surface.calculate_forces(Vec3::new(-5.0, 0.0, 30.0), 1.225, 1.0, 10.0);
+        let straight = surface.calculate_forces(Vec3::new(0.0, 0.0, 30.0), 1.225, 1.0, 10.0);
+
+        assert!(outboard.drag.x > 0.0);
+        assert!(inboard.drag.x < 0.0);
+        assert!((outboard.drag.x + inboard.drag.x).abs() < 1e-4);
+        assert_eq!(straight.drag.x, 0.0);
+    }
+
+    #[test]
+    fn load_factor_is_the_vertical_force_per_weight() {
+        let mut surface_list = AeroSurfaceList {
+            surfaces: vec![(wing(default()), Transform::IDENTITY)],
+            ..default()
+        };
+        let atmosphere = Atmosphere::default();
+        let wind = Wind::default();
+        let soaring_weather = SoaringWeather::default();
+        let ambient_air = AmbientAir {
+            atmosphere: &atmosphere,
+            wind: &wind,
+            soaring_weather: &soaring_weather,
+            time: 0.0,
+        };
+        // Banked by 60 degrees at an angle of attack of about 6 degrees.
+        let banked = Transform::from_rotation(Quat::from_rotation_z(PI / 3.0));
+        let velocity = Velocity::linear(banked.rotation * Vec3::new(0.0, -3.0, -30.0));
+        let mass = 200.0;
+        let mass_properties = MassProperties { mass, ..default() };
+        surface_list.calculate_forces(
+            &mut ExternalForce::default(),
+            &mass_properties,
+            &banked,
+            &velocity,
+            &ambient_air,
+            None,
+        );
+
+        // The lift acts along the airplane's vertical axis, not against gravity.
+        let lift = surface_list.surfaces[0].0.lift;
+        let expected = surface_list.aerodynamic_force.dot(banked.up()) / (mass * GRAVITY);
+        assert!(surface_list.load_factor > 1.0);
+        assert!((surface_list.load_factor - expected).abs() < 1e-4);
+        assert!(lift.y < surface_list.load_factor * mass * GRAVITY);
+    }
+
+    #[test]
+    fn dihedral_rolls_away_from_sideslip() {
+        let dihedral = 0.07;
+        let mut surface_list = AeroSurfaceList {
+            surfaces: vec![
+                (
+                    wing(AeroSurfaceConfig::default()),
+                    Transform::from_xyz(-5.0, 0.0, 0.0)
+                        .with_rotation(Quat::from_rotation_z(-dihedral)),
+                ),
+                (
+                    wing(AeroSurfaceConfig::default()),
+                    Transform::from_xyz(5.0, 0.0, 0.0)
+                        .with_rotation(Quat::from_rotation_z(dihedral)),
+                ),
+            ],
+            ..default()
+        };
+
+        // Slipping to the right, the air comes from the right.
+        let slipping_right = list_forces(
+            &mut surface_list,
+            Velocity::linear(Vec3::new(3.0, 0.0, -30.0)),
+        );
+        let slipping_left = list_forces(
+            &mut surface_list,
+            Velocity::linear(Vec3::new(-3.0, 0.0, -30.0)),
+        );
+
+        // The right wing gets more lift and the airplane rolls to the left (positive about z).
+        assert!(slipping_right.torque.z - slipping_left.torque.z > 1.0);
+    }
+
+    #[test]
+    fn downwash_reduces_tail_angle_of_attack() {
+        let tail_lift = |wakes: Vec<Wake>| {
+            let mut surface_list = AeroSurfaceList {
+                surfaces: vec![
+                    // Wing producing upward lift at zero angle of attack
+                    (
+                        wing(AeroSurfaceConfig {
+                            zero_lift_aoa: 0.05,
+                            ..default()
+                        }),
+                        Transform::IDENTITY,
+                    ),
+                    (
+                        AeroSurface::default(),
+                        Transform::from_xyz(0.0, 0.0, 5.0)
+                            .with_rotation(Quat::from_rotation_x(0.02)),
+                    ),
+                ],
+                wakes,
+                ..default()
+            };
+            list_forces(
+                &mut surface_list,
+                Velocity::linear(Vec3::new(0.0, 0.0, -30.0)),
+            );
+            assert!(surface_list.surfaces[0].0.lift.y > 0.0);
+            surface_list.surfaces[1].0.lift.y
+        };
+
+        let free_stream = tail_lift(Vec::new());
+        let in_wake = tail_lift(vec![Wake {
+            upstream_surface: 0,
+            downstream_surface: 1,
+            downwash_gradient: 0.1,
+        }]);
+
+        assert!(in_wake < free_stream);
+    }
+
+    #[test]
+    fn ask21_glide_ratio_matches_published_figures() {
+        let (surface_list, mass_properties) = ask21();
+
+        // Best glide ratio 1:34 at 90 km/h.
+        let clean = trimmed_glide(&surface_list, &mass_properties, 90.0 / 3.6);
+        assert!(
+            (32.0..36.0).contains(&clean.glide_ratio()),
+            "clean glide ratio {}",
+            clean.glide_ratio()
+        );
+        assert!(clean.elevator.abs() < 0.3);
+
+        // CS 22.75: with the airbrakes extended the glide must not be flatter than 1:7 at
+        // 1.3 times the stall speed (about 85 km/h).
+        let mut braked_surface_list = surface_list.clone();
+        for (surface, _) in &mut braked_surface_list.surfaces {
+            surface.airbrake_deployment = 1.0;
+        }
+        let braked = trimmed_glide(&braked_surface_list, &mass_properties, 85.0 / 3.6);
+        assert!(
+            (5.0..7.0).contains(&braked.glide_ratio()),
+            "glide ratio with airbrakes {}",
+            braked.glide_ratio()
+        );
+    }
+
+    #[test]
+    fn ask21_trimmed_speed_and_sink_rate() {
+        let (surface_list, mass_properties) = ask21();
+
+        // With the elevator neutral the ASK 21 flies at about its best glide speed.
+        let trimmed_speed = bisect(20.0, 45.0, |airspeed| {
+            trimmed_glide(&surface_list, &mass_properties, airspeed).elevator
+        }) * 3.6;
+        assert!(
+            (90.0..110.0).contains(&trimmed_speed),
+            "trimmed speed {} km/h",
+            trimmed_speed
+        );
+
+        // Minimum sink rate 0.65 m/s at 67 km/h, rising to about 1 m/s at 120 km/h.
+        let sink_rate = |speed: f32| {
+            let airspeed = speed / 3.6;
+            airspeed / trimmed_glide(&surface_list, &mass_properties, airspeed).glide_ratio()
+        };
+        let slow = sink_rate(67.0);
+        let fast = sink_rate(120.0);
+        assert!((0.6..0.8).contains(&slow), "sink rate {} m/s", slow);
+        assert!((0.9..1.2).contains(&fast), "sink rate {} m/s", fast);
+    }
+}
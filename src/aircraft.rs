@@ -0,0 +1,735 @@
+use bevy::asset::{AssetLoader, AssetPath, HandleId, LoadContext, LoadState, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::aerodynamics::{
+    ActuatorConfig, AeroSurface, AeroSurfaceConfig, AeroSurfaceList, ControlInputType, Wake,
+};
+use crate::ballast::{BallastTank, WaterBallast};
+use crate::collider::{AxisConvention, ColliderPoints, GltfColliderParts, GltfColliders};
+use crate::fuel::{FuelFeed, FuelSystem, FuelTank};
+use crate::landing_gear::{LandingGear, Strut};
+use crate::mass::{CenterOfGravityLimits, MassBalance, PayloadStation};
+use crate::model::{AnimatedPart, GltfSurfaces, ModelAnimation, PartMotion};
+use crate::powerplant::{JetEngine, PistonEngine, Powerplant, Propeller};
+use crate::structure::{StructuralLimits, Structure};
+
+pub struct AircraftPlugin;
+
+impl Plugin for AircraftPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AircraftDefinition>()
+            .init_asset_loader::<AircraftDefinitionLoader>()
+            .add_system(apply_aircraft_definitions);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SurfaceDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub config: AeroSurfaceConfig,
+    #[serde(default)]
+    pub input_type: ControlInputType,
+    #[serde(default)]
+    pub input_sensitivity: f32,
+    #[serde(default)]
+    pub actuator: ActuatorConfig,
+    pub position: Vec3,
+    #[serde(default)]
+    pub rotation: Vec3, // radians, XYZ Euler angles
+}
+
+#[derive(Deserialize)]
+pub struct MassDefinition {
+    pub empty_mass: f32,            // kg
+    pub empty_center_of_mass: Vec3, // meters
+    pub empty_inertia: Vec3,        // kg·m², about the airplane's axes
+    #[serde(default)]
+    pub stations: Vec<PayloadStation>,
+    #[serde(default)]
+    pub center_of_gravity_limits: Option<CenterOfGravityLimits>,
+}
+
+#[derive(Deserialize)]
+pub enum EngineDefinition {
+    // Piston engine driving a propeller, which thrusts forward along the mount's -z axis.
+    Piston {
+        #[serde(default)]
+        engine: PistonEngine,
+        #[serde(default)]
+        propeller: Propeller,
+        position: Vec3,
+        #[serde(default)]
+        rotation: Vec3, // radians, XYZ Euler angles
+    },
+    // Jet or turbofan engine, which thrusts forward along the mount's -z axis.
+    Jet {
+        #[serde(default)]
+        engine: JetEngine,
+        position: Vec3,
+        #[serde(default)]
+        rotation: Vec3, // radians, XYZ Euler angles
+    },
+}
+
+// Description of an airplane, loaded from a RON file with the `.aircraft` extension. Paths
+// are relative to the assets folder.
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "6a7f2c8e-3d1b-4f5a-9c2e-8b4d0e1f7a93"]
+pub struct AircraftDefinition {
+    pub name: String,
+    pub model: String,
+    #[serde(default)]
+    pub model_rotation: Vec3, // radians, XYZ Euler angles
+    pub colliders: Vec<String>,
+    #[serde(default)]
+    pub collider_axes: AxisConvention,
+    #[serde(skip)]
+    pub collider_handles: Vec<Handle<ColliderPoints>>,
+    // Colliders built from tagged nodes of the model, replacing the collider files.
+    #[serde(default)]
+    pub gltf_colliders: Option<GltfColliders>,
+    #[serde(default)]
+    pub surfaces: Vec<SurfaceDefinition>,
+    // Additional surfaces described by empties in the model, appended after `surfaces`.
+    #[serde(default)]
+    pub surfaces_from_model: bool,
+    #[serde(default)]
+    pub animated_parts: Vec<AnimatedPart>,
+    #[serde(default)]
+    pub wakes: Vec<Wake>,
+    pub mass: MassDefinition,
+    // At most one engine, as each kind of engine is a single component of the airplane.
+    #[serde(default)]
+    pub engines: Vec<EngineDefinition>,
+    // Tanks feeding the engines
+    #[serde(default)]
+    pub fuel_tanks: Vec<FuelTank>,
+    #[serde(default)]
+    pub fuel_feed: FuelFeed,
+    #[serde(default)]
+    pub ballast_tanks: Vec<BallastTank>,
+    #[serde(default)]
+    pub landing_gear: Vec<Strut>,
+    #[serde(default)]
+    pub structural_limits: Option<StructuralLimits>,
+}
+
+#[derive(Debug)]
+pub enum AircraftDefinitionError {
+    NoColliders,
+    InvalidSurface { surface: String, reason: String },
+    InvalidWake { wake: usize, reason: String },
+    InvalidAnimatedPart { node: String, reason: String },
+    InvalidMass(String),
+    InvalidEngine { engine: usize, reason: String },
+    InvalidStrut { strut: String, reason: String },
+    InvalidStructuralLimits(String),
+}
+
+impl fmt::Display for AircraftDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AircraftDefinitionError::NoColliders => {
+                write!(f, "neither colliders nor gltf_colliders given")
+            }
+            AircraftDefinitionError::InvalidSurface { surface, reason } => {
+                write!(f, "surface \"{}\": {}", surface, reason)
+            }
+            AircraftDefinitionError::InvalidWake { wake, reason } => {
+                write!(f, "wake {}: {}", wake, reason)
+            }
+            AircraftDefinitionError::InvalidAnimatedPart { node, reason } => {
+                write!(f, "animated part \"{}\": {}", node, reason)
+            }
+            AircraftDefinitionError::InvalidMass(reason) => write!(f, "mass: {}", reason),
+            AircraftDefinitionError::InvalidEngine { engine, reason } => {
+                write!(f, "engine {}: {}", engine, reason)
+            }
+            AircraftDefinitionError::InvalidStrut { strut, reason } => {
+                write!(f, "landing gear strut \"{}\": {}", strut, reason)
+            }
+            AircraftDefinitionError::InvalidStructuralLimits(reason) => {
+                write!(f, "structural limits: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AircraftDefinitionError {}
+
+fn rotation_from_euler_angles(angles: Vec3) -> Quat {
+    Quat::from_euler(EulerRot::XYZ, angles.x, angles.y, angles.z)
+}
+
+impl AircraftDefinition {
+    pub fn validate(&self) -> Result<(), AircraftDefinitionError> {
+        if self.colliders.is_empty() && self.gltf_colliders.is_none() {
+            return Err(AircraftDefinitionError::NoColliders);
+        }
+
+        for surface in &self.surfaces {
+            surface.config.validate().map_err(|reason| {
+                AircraftDefinitionError::InvalidSurface {
+                    surface: surface.name.clone(),
+                    reason: reason.to_string(),
+                }
+            })?;
+        }
+
+        // Surfaces from the model are only known once it has been spawned, the wakes are
+        // checked against them again then.
+        let surface_count = if self.surfaces_from_model {
+            usize::MAX
+        } else {
+            self.surfaces.len()
+        };
+        for (index, wake) in self.wakes.iter().enumerate() {
+            wake.validate(surface_count).map_err(|reason| {
+                AircraftDefinitionError::InvalidWake {
+                    wake: index,
+                    reason,
+                }
+            })?;
+        }
+
+        for part in &self.animated_parts {
+            let axis = match part.motion {
+                PartMotion::Hinge { axis } | PartMotion::Slide { axis, .. } => axis,
+            };
+            if axis.length() < 1e-3 {
+                return Err(AircraftDefinitionError::InvalidAnimatedPart {
+                    node: part.node.clone(),
+                    reason: "the axis must not be zero".to_string(),
+                });
+            }
+
+            // Surfaces from the model get the names of their nodes, which are only known once
+            // it has been spawned.
+            let surface = match part.input.surface() {
+                Some(surface) if !self.surfaces_from_model => surface,
+                _ => continue,
+            };
+            if !self
+                .surfaces
+                .iter()
+                .any(|definition| definition.name == surface)
+            {
+                return Err(AircraftDefinitionError::InvalidAnimatedPart {
+                    node: part.node.clone(),
+                    reason: format!("there is no surface named \"{}\"", surface),
+                });
+            }
+        }
+
+        let mass = &self.mass;
+        if mass.empty_mass <= 0.0 {
+            return Err(AircraftDefinitionError::InvalidMass(
+                "empty_mass must be positive".to_string(),
+            ));
+        }
+        if mass.empty_inertia.min_element() <= 0.0 {
+            return Err(AircraftDefinitionError::InvalidMass(
+                "empty_inertia must be positive".to_string(),
+            ));
+        }
+        if let Some(station) = mass.stations.iter().find(|station| station.mass < 0.0) {
+            return Err(AircraftDefinitionError::InvalidMass(format!(
+                "station \"{}\" has a negative mass",
+                station.name
+            )));
+        }
+        if let Some(limits) = mass.center_of_gravity_limits {
+            if limits.forward >= limits.aft {
+                return Err(AircraftDefinitionError::InvalidMass(
+                    "the forward center of gravity limit must be ahead of the aft limit"
+                        .to_string(),
+                ));
+            }
+        }
+        if let Some(tank) = self
+            .fuel_tanks
+            .iter()
+            .find(|tank| tank.quantity < 0.0 || tank.quantity > tank.capacity)
+        {
+            return Err(AircraftDefinitionError::InvalidMass(format!(
+                "fuel tank \"{}\" must hold between 0 and {} kg",
+                tank.name, tank.capacity
+            )));
+        }
+        if let Some(tank) = self
+            .ballast_tanks
+            .iter()
+            .find(|tank| tank.quantity < 0.0 || tank.quantity > tank.capacity)
+        {
+            return Err(AircraftDefinitionError::InvalidMass(format!(
+                "ballast tank \"{}\" must hold between 0 and {} kg",
+                tank.name, tank.capacity
+            )));
+        }
+
+        for (index, engine) in self.engines.iter().enumerate() {
+            let reason = if index > 0 {
+                Some("only one engine is supported")
+            } else {
+                match engine {
+                    EngineDefinition::Piston {
+                        engine, propeller, ..
+                    } => {
+                        if engine.max_power <= 0.0 || engine.starter_torque < 0.0 {
+                            Some("max_power must be positive, starter_torque not negative")
+                        } else if engine.idle_rpm <= 0.0 || engine.rated_rpm <= engine.idle_rpm {
+                            Some("rated_rpm must be above a positive idle_rpm")
+                        } else if propeller.diameter <= 0.0 || propeller.inertia <= 0.0 {
+                            Some("the propeller's diameter and inertia must be positive")
+                        } else if propeller.zero_thrust_advance_ratio <= 0.0 {
+                            Some("the propeller's zero_thrust_advance_ratio must be positive")
+                        } else {
+                            None
+                        }
+                    }
+                    EngineDefinition::Jet { engine, .. } => {
+                        if engine.max_thrust <= 0.0 || engine.afterburner_thrust < 0.0 {
+                            Some("max_thrust must be positive, afterburner_thrust not negative")
+                        } else if engine.idle_n1 <= 0.0 || engine.idle_n1 >= 1.0 {
+                            Some("idle_n1 must be between 0 and 1")
+                        } else if engine.starter_n1 <= 0.0 || engine.starter_n1 > engine.idle_n1 {
+                            Some("starter_n1 must be positive and at most idle_n1")
+                        } else if engine.spool_up_time <= 0.0
+                            || engine.spool_down_time <= 0.0
+                            || engine.afterburner_time <= 0.0
+                        {
+                            Some("the spool and afterburner times must be positive")
+                        } else if engine.afterburner_detent <= 0.0
+                            || engine.afterburner_detent >= 1.0
+                        {
+                            Some("afterburner_detent must be between 0 and 1")
+                        } else {
+                            None
+                        }
+                    }
+                }
+            };
+            if let Some(reason) = reason {
+                return Err(AircraftDefinitionError::InvalidEngine {
+                    engine: index,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        for strut in &self.landing_gear {
+            let reason = if strut.length <= 0.0 {
+                Some("length must be positive")
+            } else if strut.stiffness <= 0.0 || strut.damping < 0.0 {
+                Some("stiffness must be positive and damping must not be negative")
+            } else if strut.friction_coefficient < 0.0
+                || strut.rolling_resistance < 0.0
+                || strut.brake_coefficient < 0.0
+            {
+                Some("friction coefficients must not be negative")
+            } else if strut.steering_angle.abs() >= std::f32::consts::FRAC_PI_2 {
+                Some("steering_angle must be between -pi / 2 and pi / 2")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                return Err(AircraftDefinitionError::InvalidStrut {
+                    strut: strut.name.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        if let Some(limits) = &self.structural_limits {
+            let reason = if limits.manoeuvring_speed <= 0.0
+                || limits.never_exceed_speed < limits.manoeuvring_speed
+            {
+                Some(
+                    "speeds must be positive, never_exceed_speed at least manoeuvring_speed"
+                        .to_string(),
+                )
+            } else if limits.max_load_factor <= 0.0 || limits.min_load_factor >= 0.0 {
+                Some("max_load_factor must be positive and min_load_factor negative".to_string())
+            } else if limits.ultimate_factor < 1.0 {
+                Some("ultimate_factor must be at least 1".to_string())
+            } else {
+                limits.breakable_surfaces.iter().find_map(|breakable| {
+                    // Surfaces and colliders from the model are only known once it has been
+                    // spawned.
+                    if breakable.surface >= self.surfaces.len() && !self.surfaces_from_model {
+                        Some(format!(
+                            "breakable surface {} out of range",
+                            breakable.surface
+                        ))
+                    } else if breakable.collider.map_or(false, |collider| {
+                        collider >= self.colliders.len() && self.gltf_colliders.is_none()
+                    }) {
+                        Some(format!(
+                            "collider part of breakable surface {} out of range",
+                            breakable.surface
+                        ))
+                    } else if breakable.mass <= 0.0 || breakable.mass >= self.mass.empty_mass {
+                        Some(format!(
+                            "breakable surface {} must have a positive mass below empty_mass",
+                            breakable.surface
+                        ))
+                    } else {
+                        None
+                    }
+                })
+            };
+            if let Some(reason) = reason {
+                return Err(AircraftDefinitionError::InvalidStructuralLimits(reason));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn aero_surface_list(&self) -> AeroSurfaceList {
+        AeroSurfaceList {
+            surfaces: self
+                .surfaces
+                .iter()
+                .map(|surface| {
+                    (
+                        AeroSurface {
+                            name: surface.name.clone(),
+                            config: surface.config,
+                            input_type: surface.input_type,
+                            input_sensitivity: surface.input_sensitivity,
+                            actuator: surface.actuator,
+                            ..default()
+                        },
+                        Transform::from_translation(surface.position)
+                            .with_rotation(rotation_from_euler_angles(surface.rotation)),
+                    )
+                })
+                .collect(),
+            wakes: self.wakes.clone(),
+            ..default()
+        }
+    }
+
+    pub fn mass_balance(&self) -> MassBalance {
+        MassBalance {
+            empty_mass: MassProperties {
+                local_center_of_mass: self.mass.empty_center_of_mass,
+                mass: self.mass.empty_mass,
+                principal_inertia_local_frame: Quat::IDENTITY,
+                principal_inertia: self.mass.empty_inertia,
+            },
+            stations: self.mass.stations.clone(),
+            center_of_gravity_limits: self.mass.center_of_gravity_limits,
+            invalid_loading: false,
+        }
+    }
+
+    pub fn powerplant(&self) -> Option<Powerplant> {
+        self.engines.iter().find_map(|engine| match engine {
+            EngineDefinition::Piston {
+                engine,
+                propeller,
+                position,
+                rotation,
+            } => Some(Powerplant::new(
+                *engine,
+                *propeller,
+                Transform::from_translation(*position)
+                    .with_rotation(rotation_from_euler_angles(*rotation)),
+            )),
+            EngineDefinition::Jet { .. } => None,
+        })
+    }
+
+    pub fn jet_engine(&self) -> Option<JetEngine> {
+        self.engines.iter().find_map(|engine| match engine {
+            EngineDefinition::Jet {
+                engine,
+                position,
+                rotation,
+            } => {
+                let mut jet_engine = JetEngine {
+                    mount: Transform::from_translation(*position)
+                        .with_rotation(rotation_from_euler_angles(*rotation)),
+                    ..engine.clone()
+                };
+                jet_engine.reset();
+                Some(jet_engine)
+            }
+            EngineDefinition::Piston { .. } => None,
+        })
+    }
+
+    // Compound collider with one part per collider file, in the order of `colliders`, so that
+    // parts can be referred to by index. None unless every file has been loaded, as parry
+    // can't build an empty compound.
+    pub fn collider(&self, collider_points: &Assets<ColliderPoints>) -> Option<Collider> {
+        let parts = self
+            .collider_handles
+            .iter()
+            .map(|handle| {
+                collider_points.get(handle).map(|points| {
+                    let (position, collider) = points.collider(self.collider_axes);
+                    (position, Quat::IDENTITY, collider)
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if parts.is_empty() {
+            None
+        } else {
+            Some(Collider::compound(parts))
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct AircraftDefinitionLoader;
+
+impl AssetLoader for AircraftDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let mut definition: AircraftDefinition = ron::de::from_bytes(bytes)?;
+            definition.validate()?;
+
+            // The collider files are loaded as dependencies of the definition.
+            let collider_paths = definition
+                .colliders
+                .iter()
+                .map(|path| AssetPath::new(PathBuf::from(path), None))
+                .collect::<Vec<_>>();
+            definition.collider_handles = collider_paths
+                .iter()
+                .map(|path| load_context.get_handle(path.clone()))
+                .collect();
+
+            load_context
+                .set_default_asset(LoadedAsset::new(definition).with_dependencies(collider_paths));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aircraft"]
+    }
+}
+
+// (Re)builds the surfaces, colliders, mass properties and model of every airplane using a
+// definition once it and its collider files are loaded, and again whenever one of these
+// files changes on disk. The airplane keeps its position and velocity.
+#[allow(clippy::too_many_arguments)]
+fn apply_aircraft_definitions(
+    mut commands: Commands,
+    mut definition_events: EventReader<AssetEvent<AircraftDefinition>>,
+    mut collider_events: EventReader<AssetEvent<ColliderPoints>>,
+    mut pending_definitions: Local<Vec<HandleId>>,
+    definitions: Res<Assets<AircraftDefinition>>,
+    collider_points: Res<Assets<ColliderPoints>>,
+    airplane_query: Query<(
+        Entity,
+        &Handle<AircraftDefinition>,
+        ChangeTrackers<Handle<AircraftDefinition>>,
+    )>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in definition_events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            if !pending_definitions.contains(&handle.id()) {
+                pending_definitions.push(handle.id());
+            }
+        }
+    }
+    for event in collider_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            for (id, definition) in definitions.iter() {
+                if definition.collider_handles.contains(handle)
+                    && !pending_definitions.contains(&id)
+                {
+                    pending_definitions.push(id);
+                }
+            }
+        }
+    }
+
+    // A definition handle that is inserted again, e.g. when the airplane is reset, reapplies
+    // the definition.
+    for (_, handle, change_trackers) in &airplane_query {
+        if change_trackers.is_changed() && !pending_definitions.contains(&handle.id()) {
+            pending_definitions.push(handle.id());
+        }
+    }
+
+    pending_definitions.retain(|&id| {
+        let definition = match definitions.get(&Handle::weak(id)) {
+            Some(definition) => definition,
+            None => return false,
+        };
+
+        // Wait for the collider files, unless one of them failed to load.
+        let mut failed = false;
+        for handle in &definition.collider_handles {
+            match asset_server.get_load_state(handle) {
+                LoadState::Failed => failed = true,
+                _ if collider_points.get(handle).is_none() => return true,
+                _ => {}
+            }
+        }
+        if failed {
+            error!(
+                "Aircraft definition \"{}\" is missing colliders that failed to load, keeping \
+                 the previous collider",
+                definition.name
+            );
+        }
+
+        for (entity, airplane_handle, _) in &airplane_query {
+            if airplane_handle.id() != id {
+                continue;
+            }
+            info!("Applying aircraft definition \"{}\"", definition.name);
+
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.despawn_descendants();
+            entity_commands
+                .insert((
+                    Name::new(definition.name.clone()),
+                    definition.aero_surface_list(),
+                    definition.mass_balance(),
+                ))
+                .with_children(|child_builder| {
+                    child_builder.spawn(SceneBundle {
+                        scene: asset_server.load(definition.model.as_str()),
+                        transform: Transform::from_rotation(rotation_from_euler_angles(
+                            definition.model_rotation,
+                        )),
+                        ..default()
+                    });
+                });
+            if definition.surfaces_from_model {
+                entity_commands.insert(GltfSurfaces);
+            }
+            if definition.animated_parts.is_empty() {
+                entity_commands.remove::<ModelAnimation>();
+            } else {
+                entity_commands.insert(ModelAnimation::new(definition.animated_parts.clone()));
+            }
+            if let Some(gltf_colliders) = &definition.gltf_colliders {
+                // built once the model has been spawned
+                entity_commands.insert(gltf_colliders.clone());
+            } else if let Some(collider) = definition.collider(&collider_points) {
+                entity_commands
+                    .insert(collider)
+                    .remove::<GltfColliderParts>();
+            }
+            match definition.powerplant() {
+                Some(powerplant) => {
+                    entity_commands.insert(powerplant);
+                }
+                None => {
+                    entity_commands.remove::<Powerplant>();
+                }
+            }
+            match definition.jet_engine() {
+                Some(jet_engine) => {
+                    entity_commands.insert(jet_engine);
+                }
+                None => {
+                    entity_commands.remove::<JetEngine>();
+                }
+            }
+            if definition.fuel_tanks.is_empty() {
+                entity_commands.remove::<FuelSystem>();
+            } else {
+                entity_commands.insert(FuelSystem {
+                    tanks: definition.fuel_tanks.clone(),
+                    feed: definition.fuel_feed,
+                });
+            }
+            if definition.ballast_tanks.is_empty() {
+                entity_commands.remove::<WaterBallast>();
+            } else {
+                entity_commands.insert(WaterBallast {
+                    tanks: definition.ballast_tanks.clone(),
+                    ..default()
+                });
+            }
+            match &definition.structural_limits {
+                Some(limits) => {
+                    entity_commands.insert(Structure::new(limits.clone()));
+                }
+                None => {
+                    entity_commands.remove::<Structure>();
+                }
+            }
+            if definition.landing_gear.is_empty() {
+                entity_commands.remove::<LandingGear>();
+            } else {
+                entity_commands.insert(LandingGear {
+                    struts: definition.landing_gear.clone(),
+                    ..default()
+                });
+            }
+        }
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PartInput;
+
+    fn ask21() -> AircraftDefinition {
+        ron::from_str(include_str!("../assets/planes/ask21/ask21.aircraft")).unwrap()
+    }
+
+    fn with_part(input: PartInput, motion: PartMotion) -> AircraftDefinition {
+        let mut definition = ask21();
+        definition.animated_parts.push(AnimatedPart {
+            node: "Part".to_string(),
+            input,
+            motion,
+        });
+        definition
+    }
+
+    #[test]
+    fn animated_parts_need_an_axis_and_a_surface() {
+        let aileron = PartInput::ControlSurface("Left wing".to_string());
+        let hinge = PartMotion::Hinge { axis: Vec3::X };
+        assert!(with_part(aileron.clone(), hinge).validate().is_ok());
+        assert!(with_part(PartInput::Canopy, hinge).validate().is_ok());
+
+        let zero_axes = [
+            PartMotion::Hinge { axis: Vec3::ZERO },
+            PartMotion::Slide {
+                axis: Vec3::ZERO,
+                distance: 0.1,
+            },
+        ];
+        for motion in zero_axes {
+            assert!(matches!(
+                with_part(aileron.clone(), motion).validate(),
+                Err(AircraftDefinitionError::InvalidAnimatedPart { .. })
+            ));
+            assert!(with_part(PartInput::Canopy, motion).validate().is_err());
+        }
+
+        let airbrake = PartInput::Airbrake("Wing".to_string());
+        assert!(matches!(
+            with_part(airbrake, hinge).validate(),
+            Err(AircraftDefinitionError::InvalidAnimatedPart { .. })
+        ));
+    }
+}
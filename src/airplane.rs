@@ -3,6 +3,10 @@ use bevy_rapier3d::prelude::*;
 use enum_map::EnumMap;
 
 use crate::aerodynamics::AeroSurfaceList;
+use crate::controls::{Canopy, FlapLever, ThrottleLever, Trim};
+use crate::fuel::FuelSystem;
+use crate::structure::Structure;
+use crate::wind::Wind;
 
 pub struct AirplanePlugin;
 
@@ -21,6 +25,13 @@ pub enum FlightMetric {
     Airspeed,
     VerticalSpeed,
     Height,
+    Flaps,
+    PitchTrim,
+    YawTrim,
+    Fuel,
+    LoadFactor,
+    GMeterMax,
+    GMeterMin,
 }
 
 #[derive(Component, Clone)]
@@ -45,6 +56,10 @@ pub struct AirplaneBundle {
     pub velocity: Velocity,
     pub collider: Collider,
     pub aero_surface_list: AeroSurfaceList,
+    pub flap_lever: FlapLever,
+    pub trim: Trim,
+    pub throttle_lever: ThrottleLever,
+    pub canopy: Canopy,
     pub read_mass_properties: ReadMassProperties,
     pub metrics: FlightMetrics,
     pub transform: Transform,
@@ -62,9 +77,11 @@ impl Default for AirplaneBundle {
             external_force: ExternalForce::default(),
             velocity: Velocity::default(),
             collider: Collider::cuboid(0.5, 0.5, 0.5),
-            aero_surface_list: AeroSurfaceList {
-                surfaces: Vec::new(),
-            },
+            aero_surface_list: AeroSurfaceList::default(),
+            flap_lever: FlapLever::default(),
+            trim: Trim::default(),
+            throttle_lever: ThrottleLever::default(),
+            canopy: Canopy::default(),
             read_mass_properties: ReadMassProperties::default(),
             metrics: FlightMetrics::default(),
             transform: Transform::default(),
@@ -76,12 +93,49 @@ impl Default for AirplaneBundle {
 }
 
 fn update_flight_metrics(
-    mut airplane_query: Query<(&mut FlightMetrics, &Transform, &Velocity), With<Airplane>>,
+    mut airplane_query: Query<
+        (
+            &mut FlightMetrics,
+            &Transform,
+            &Velocity,
+            &AeroSurfaceList,
+            Option<&FlapLever>,
+            Option<&Trim>,
+            Option<&FuelSystem>,
+            Option<&Structure>,
+        ),
+        With<Airplane>,
+    >,
+    wind: Res<Wind>,
+    time: Res<Time>,
 ) {
-    for (mut metrics, transform, velocity) in &mut airplane_query {
+    for (
+        mut metrics,
+        transform,
+        velocity,
+        surface_list,
+        flap_lever,
+        trim,
+        fuel_system,
+        structure,
+    ) in &mut airplane_query
+    {
+        let true_air_velocity =
+            velocity.linvel - wind.wind_velocity(transform.translation, time.elapsed_seconds());
         metrics.metrics[FlightMetric::Airspeed] =
-            velocity.linvel.length() * velocity.linvel.normalize().dot(transform.forward());
+            true_air_velocity.length() * true_air_velocity.normalize().dot(transform.forward());
         metrics.metrics[FlightMetric::VerticalSpeed] = velocity.linvel.y;
         metrics.metrics[FlightMetric::Height] = transform.translation.y;
+        metrics.metrics[FlightMetric::Flaps] =
+            flap_lever.map_or(0.0, |flap_lever| flap_lever.angle.to_degrees());
+        metrics.metrics[FlightMetric::PitchTrim] = trim.map_or(0.0, |trim| trim.pitch);
+        metrics.metrics[FlightMetric::YawTrim] = trim.map_or(0.0, |trim| trim.yaw);
+        metrics.metrics[FlightMetric::Fuel] =
+            fuel_system.map_or(0.0, |fuel_system| fuel_system.quantity());
+        metrics.metrics[FlightMetric::LoadFactor] = surface_list.load_factor;
+        metrics.metrics[FlightMetric::GMeterMax] =
+            structure.map_or(surface_list.load_factor, |structure| structure.g_meter_max);
+        metrics.metrics[FlightMetric::GMeterMin] =
+            structure.map_or(surface_list.load_factor, |structure| structure.g_meter_min);
     }
 }
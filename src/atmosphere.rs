@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+
+pub struct AtmospherePlugin;
+
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Atmosphere>()
+            .init_resource::<Atmosphere>();
+    }
+}
+
+pub const GRAVITY: f32 = 9.80665; // m/s²
+const GAS_CONSTANT: f32 = 287.05287; // J/(kg·K), specific gas constant of dry air
+const HEAT_CAPACITY_RATIO: f32 = 1.4;
+
+pub const ISA_SEA_LEVEL_TEMPERATURE: f32 = 288.15; // K
+pub const ISA_SEA_LEVEL_PRESSURE: f32 = 101_325.0; // Pa
+pub const ISA_SEA_LEVEL_DENSITY: f32 = 1.225; // kg/m³
+
+// International Standard Atmosphere layers as (base geopotential altitude, lapse rate).
+const LAYERS: [(f32, f32); 7] = [
+    (0.0, -0.0065),
+    (11_000.0, 0.0),
+    (20_000.0, 0.001),
+    (32_000.0, 0.0028),
+    (47_000.0, 0.0),
+    (51_000.0, -0.0028),
+    (71_000.0, -0.002),
+];
+const TOP_OF_ATMOSPHERE: f32 = 84_852.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AtmosphereSample {
+    pub temperature: f32,    // K
+    pub pressure: f32,       // Pa
+    pub density: f32,        // kg/m³
+    pub speed_of_sound: f32, // m/s
+}
+
+#[derive(Resource, Reflect, Clone, Copy)]
+#[reflect(Resource)]
+pub struct Atmosphere {
+    pub sea_level_temperature: f32, // K
+    pub sea_level_pressure: f32,    // Pa, QNH
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            sea_level_temperature: ISA_SEA_LEVEL_TEMPERATURE,
+            sea_level_pressure: ISA_SEA_LEVEL_PRESSURE,
+        }
+    }
+}
+
+impl Atmosphere {
+    pub fn sample(&self, altitude: f32) -> AtmosphereSample {
+        let altitude = altitude.clamp(LAYERS[0].0, TOP_OF_ATMOSPHERE);
+
+        // Walk up through the layers, carrying temperature and pressure from one layer
+        // base to the next.
+        let mut base_temperature = self.sea_level_temperature;
+        let mut base_pressure = self.sea_level_pressure;
+        let mut temperature = base_temperature;
+        let mut pressure = base_pressure;
+        for (index, &(base_altitude, lapse_rate)) in LAYERS.iter().enumerate() {
+            let top_altitude = LAYERS
+                .get(index + 1)
+                .map_or(TOP_OF_ATMOSPHERE, |&(altitude, _)| altitude);
+            let height = altitude.min(top_altitude) - base_altitude;
+
+            temperature = base_temperature + lapse_rate * height;
+            pressure = if lapse_rate == 0.0 {
+                base_pressure * (-GRAVITY * height / (GAS_CONSTANT * base_temperature)).exp()
+            } else {
+                base_pressure
+                    * (temperature / base_temperature).powf(-GRAVITY / (lapse_rate * GAS_CONSTANT))
+            };
+
+            if altitude <= top_altitude {
+                break;
+            }
+            base_temperature = temperature;
+            base_pressure = pressure;
+        }
+
+        AtmosphereSample {
+            temperature,
+            pressure,
+            density: pressure / (GAS_CONSTANT * temperature),
+            speed_of_sound: (HEAT_CAPACITY_RATIO * GAS_CONSTANT * temperature).sqrt(),
+        }
+    }
+
+    pub fn density(&self, altitude: f32) -> f32 {
+        self.sample(altitude).density
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_relative_eq(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            ((actual - expected) / expected).abs() < tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn matches_isa_table() {
+        let atmosphere = Atmosphere::default();
+        // (altitude m, temperature K, pressure Pa, density kg/m³)
+        let table = [
+            (0.0, 288.15, 101_325.0, 1.2250),
+            (1_000.0, 281.65, 89_874.6, 1.1117),
+            (11_000.0, 216.65, 22_632.1, 0.36392),
+            (20_000.0, 216.65, 5_474.89, 0.088035),
+        ];
+        for (altitude, temperature, pressure, density) in table {
+            let sample = atmosphere.sample(altitude);
+            assert_relative_eq(sample.temperature, temperature, 1e-4);
+            assert_relative_eq(sample.pressure, pressure, 1e-3);
+            assert_relative_eq(sample.density, density, 1e-3);
+            assert_relative_eq(atmosphere.density(altitude), density, 1e-3);
+        }
+    }
+
+    #[test]
+    fn qnh_scales_pressure_and_density() {
+        let standard = Atmosphere::default();
+        let high_pressure = Atmosphere {
+            sea_level_pressure: 103_000.0,
+            ..default()
+        };
+        let ratio = 103_000.0 / ISA_SEA_LEVEL_PRESSURE;
+        for altitude in [0.0, 1_000.0, 11_000.0] {
+            assert_relative_eq(
+                high_pressure.sample(altitude).pressure,
+                standard.sample(altitude).pressure * ratio,
+                1e-4,
+            );
+            assert_relative_eq(
+                high_pressure.density(altitude),
+                standard.density(altitude) * ratio,
+                1e-4,
+            );
+        }
+    }
+}
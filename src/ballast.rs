@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::input::{AirplaneAction, AirplaneControls};
+
+pub struct BallastPlugin;
+
+impl Plugin for BallastPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WaterBallast>()
+            .add_system(dump_water_ballast);
+    }
+}
+
+#[derive(Reflect, FromReflect, Deserialize, Clone)]
+pub struct BallastTank {
+    pub name: String,
+    pub capacity: f32,  // kg
+    pub quantity: f32,  // kg
+    pub position: Vec3, // relative to the airplane
+}
+
+// Water ballast tanks, typically in the wings, sharing one dump valve. The water drains by
+// gravity, so the flow decreases with the water level (Torricelli's law).
+#[derive(Reflect, Component, Clone)]
+pub struct WaterBallast {
+    pub tanks: Vec<BallastTank>,
+    pub dump_rate: f32, // kg/s per tank when full
+    pub valve_open: bool,
+}
+
+impl Default for WaterBallast {
+    fn default() -> Self {
+        Self {
+            tanks: Vec::new(),
+            dump_rate: 0.5,
+            valve_open: false,
+        }
+    }
+}
+
+pub fn dump_water_ballast(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut ballast_query: Query<&mut WaterBallast>,
+    time: Res<Time>,
+) {
+    let action_state = input_query.single();
+
+    for mut water_ballast in &mut ballast_query {
+        if action_state.just_pressed(AirplaneAction::DumpBallast) {
+            water_ballast.valve_open = !water_ballast.valve_open;
+        }
+
+        // Only touch the tanks while water is flowing, so the mass properties are not
+        // recomputed needlessly.
+        let flowing = water_ballast.tanks.iter().any(|tank| tank.quantity > 0.0);
+        if !water_ballast.valve_open || !flowing {
+            continue;
+        }
+
+        let dump_rate = water_ballast.dump_rate;
+        for tank in &mut water_ballast.tanks {
+            if tank.capacity <= 0.0 {
+                continue;
+            }
+            let flow = dump_rate * (tank.quantity / tank.capacity).max(0.0).sqrt();
+            tank.quantity = (tank.quantity - flow * time.delta_seconds()).max(0.0);
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::gltf::GltfExtras;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::scene::SceneInstance;
+use bevy::utils::BoxedFuture;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+use std::ops::Range;
+
+use crate::model::{spawned_scenes, visit_hierarchy, visit_scenes};
+use crate::structure::Structure;
+
+pub struct ColliderPlugin;
+
+impl Plugin for ColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ColliderPoints>()
+            .init_asset_loader::<ColliderPointsLoader>()
+            .add_system(build_gltf_colliders);
+    }
+}
+
+// Point cloud of a convex collider, one point per line as `x;y;z`. The points are kept in
+// the file's coordinate system; see `AxisConvention`.
+#[derive(TypeUuid)]
+#[uuid = "0c3e5a71-9b2d-4e86-a4f1-5d7c2b8e9f30"]
+pub struct ColliderPoints {
+    pub points: Vec<Vec3>,
+}
+
+#[derive(Debug)]
+pub enum ColliderLoadError {
+    InvalidUtf8,
+    WrongValueCount { line: usize, count: usize },
+    InvalidNumber { line: usize, value: String },
+    NoPoints,
+}
+
+impl fmt::Display for ColliderLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColliderLoadError::InvalidUtf8 => write!(f, "file is not valid UTF-8"),
+            ColliderLoadError::WrongValueCount { line, count } => write!(
+                f,
+                "line {}: expected 3 values separated by ';', found {}",
+                line, count
+            ),
+            ColliderLoadError::InvalidNumber { line, value } => {
+                write!(f, "line {}: \"{}\" is not a number", line, value)
+            }
+            ColliderLoadError::NoPoints => write!(f, "file contains no points"),
+        }
+    }
+}
+
+impl std::error::Error for ColliderLoadError {}
+
+impl ColliderPoints {
+    pub fn parse(text: &str) -> Result<Self, ColliderLoadError> {
+        let mut points = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let values = line.split(';').map(str::trim).collect::<Vec<_>>();
+            if values.len() != 3 {
+                return Err(ColliderLoadError::WrongValueCount {
+                    line: line_number,
+                    count: values.len(),
+                });
+            }
+            let mut components = [0.0; 3];
+            for (component, value) in components.iter_mut().zip(values) {
+                *component =
+                    value
+                        .parse::<f32>()
+                        .map_err(|_| ColliderLoadError::InvalidNumber {
+                            line: line_number,
+                            value: value.to_string(),
+                        })?;
+            }
+            points.push(Vec3::from_array(components));
+        }
+
+        if points.is_empty() {
+            return Err(ColliderLoadError::NoPoints);
+        }
+        Ok(Self { points })
+    }
+
+    // Convex hull of the points, converted to the airplane's coordinate system. Degenerate
+    // point clouds (e.g. all points in one plane) have no hull; they fall back to their
+    // bounding box.
+    pub fn collider(&self, axes: AxisConvention) -> (Vec3, Collider) {
+        let points = self
+            .points
+            .iter()
+            .map(|&point| axes.convert(point))
+            .collect::<Vec<_>>();
+        convex_hull_or_bounding_box(&points)
+    }
+}
+
+fn convex_hull_or_bounding_box(points: &[Vec3]) -> (Vec3, Collider) {
+    if let Some(collider) = Collider::convex_hull(points) {
+        return (Vec3::ZERO, collider);
+    }
+
+    warn!("Collider points have no convex hull, using their bounding box instead");
+    let min = points
+        .iter()
+        .fold(Vec3::splat(f32::INFINITY), |a, &b| a.min(b));
+    let max = points
+        .iter()
+        .fold(Vec3::splat(f32::NEG_INFINITY), |a, &b| a.max(b));
+    let half_extents = ((max - min) * 0.5).max(Vec3::splat(0.01));
+    (
+        (min + max) * 0.5,
+        Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+    )
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum Axis {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl Axis {
+    fn component(&self, point: Vec3) -> f32 {
+        match self {
+            Axis::PositiveX => point.x,
+            Axis::NegativeX => -point.x,
+            Axis::PositiveY => point.y,
+            Axis::NegativeY => -point.y,
+            Axis::PositiveZ => point.z,
+            Axis::NegativeZ => -point.z,
+        }
+    }
+}
+
+// For each axis of the airplane's coordinate system (x right, y up, z back), the axis of
+// the file it is read from.
+#[derive(Deserialize, Clone, Copy)]
+pub struct AxisConvention {
+    pub x: Axis,
+    pub y: Axis,
+    pub z: Axis,
+}
+
+impl Default for AxisConvention {
+    fn default() -> Self {
+        Self {
+            x: Axis::PositiveX,
+            y: Axis::PositiveY,
+            z: Axis::PositiveZ,
+        }
+    }
+}
+
+impl AxisConvention {
+    pub fn convert(&self, point: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.component(point),
+            self.y.component(point),
+            self.z.component(point),
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct ColliderPointsLoader;
+
+impl AssetLoader for ColliderPointsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let text = std::str::from_utf8(bytes).map_err(|_| ColliderLoadError::InvalidUtf8)?;
+            let collider_points = ColliderPoints::parse(text)?;
+            load_context.set_default_asset(LoadedAsset::new(collider_points));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["csv"]
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub enum ColliderShape {
+    // One convex hull around all vertices of a node.
+    #[default]
+    ConvexHull,
+    // Approximate convex decomposition (V-HACD) of the node's triangle mesh, for concave
+    // parts.
+    ConvexDecomposition,
+}
+
+// Builds the airplane's compound collider from the nodes of its glTF model that are tagged
+// as collision parts, either by containing `tag` in their name or by having `tag` set in
+// their extras (e.g. `{"collider": true}`). The tagged nodes are hidden. The component is
+// removed once the collider has been built.
+#[derive(Deserialize, Component, Clone)]
+pub struct GltfColliders {
+    #[serde(default = "GltfColliders::default_tag")]
+    pub tag: String,
+    #[serde(default)]
+    pub shape: ColliderShape,
+}
+
+impl GltfColliders {
+    fn default_tag() -> String {
+        "collider".to_string()
+    }
+
+    fn is_tagged(&self, name: Option<&Name>, extras: Option<&GltfExtras>) -> bool {
+        let tagged_by_name = name.map_or(false, |name| name.as_str().contains(&self.tag));
+        let tagged_by_extras = extras.map_or(false, |extras| {
+            serde_json::from_str::<serde_json::Value>(&extras.value).map_or(false, |value| {
+                value
+                    .get(&self.tag)
+                    .map_or(false, |tag| tag != &serde_json::Value::Bool(false))
+            })
+        });
+        tagged_by_name || tagged_by_extras
+    }
+}
+
+// Parts of the compound collider built from each tagged node, in the order the nodes were
+// found. A convex decomposition gives a node several parts, as compounds can't be nested.
+#[derive(Component, Clone, Default)]
+pub struct GltfColliderParts {
+    pub node_parts: Vec<Range<usize>>,
+}
+
+// Triangle mesh of a tagged node, in the airplane's coordinate system.
+#[derive(Default)]
+struct ColliderMesh {
+    vertices: Vec<Vec3>,
+    indices: Vec<[u32; 3]>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_gltf_colliders(
+    mut commands: Commands,
+    mut airplane_query: Query<(Entity, &GltfColliders, &Children, Option<&mut Structure>)>,
+    hierarchy_query: Query<(&Transform, Option<&Children>)>,
+    tag_query: Query<(Option<&Name>, Option<&GltfExtras>)>,
+    mesh_query: Query<&Handle<Mesh>>,
+    scene_query: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, gltf_colliders, children, structure) in &mut airplane_query {
+        let scenes = match spawned_scenes(children, &scene_query, &scene_spawner) {
+            Some(scenes) => scenes,
+            None => continue,
+        };
+
+        let mut tagged_nodes = Vec::new();
+        visit_scenes(&scenes, &hierarchy_query, &mut |node, transform| {
+            if let Ok((name, extras)) = tag_query.get(node) {
+                if gltf_colliders.is_tagged(name, extras) {
+                    tagged_nodes.push((node, transform));
+                }
+            }
+        });
+
+        // The meshes of a tagged node and its descendants (e.g. its mesh primitives) make up
+        // one collider part, or several for a convex decomposition. A node without meshes
+        // has none, but keeps its place in `node_parts`.
+        let mut parts = Vec::new();
+        let mut node_parts = Vec::new();
+        for (node, transform) in tagged_nodes {
+            commands
+                .entity(node)
+                .insert(Visibility { is_visible: false });
+
+            let mut collider_mesh = ColliderMesh::default();
+            visit_hierarchy(node, transform, &hierarchy_query, &mut |node, transform| {
+                if let Some(mesh) = mesh_query
+                    .get(node)
+                    .ok()
+                    .and_then(|handle| meshes.get(handle))
+                {
+                    append_mesh(&mut collider_mesh, mesh, &transform);
+                }
+            });
+            let first_part = parts.len();
+            if collider_mesh.vertices.is_empty() {
+                node_parts.push(first_part..first_part);
+                continue;
+            }
+
+            match gltf_colliders.shape {
+                ColliderShape::ConvexHull => {
+                    let (position, collider) = convex_hull_or_bounding_box(&collider_mesh.vertices);
+                    parts.push((position, Quat::IDENTITY, collider));
+                }
+                ColliderShape::ConvexDecomposition => {
+                    let decomposition = Collider::convex_decomposition(
+                        &collider_mesh.vertices,
+                        &collider_mesh.indices,
+                    );
+                    match decomposition.raw.as_compound() {
+                        Some(compound) => {
+                            parts.extend(compound.shapes().iter().map(|(isometry, shape)| {
+                                (
+                                    isometry.translation.vector.into(),
+                                    isometry.rotation.into(),
+                                    Collider::from(shape.clone()),
+                                )
+                            }))
+                        }
+                        None => parts.push((Vec3::ZERO, Quat::IDENTITY, decomposition)),
+                    }
+                }
+            }
+            node_parts.push(first_part..parts.len());
+        }
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<GltfColliders>();
+        if parts.is_empty() {
+            warn!(
+                "The model contains no meshes tagged \"{}\", keeping the previous collider",
+                gltf_colliders.tag
+            );
+            continue;
+        }
+
+        // Breakable surfaces refer to the tagged nodes, which are only known now.
+        if let Some(mut structure) = structure {
+            for breakable in &mut structure.limits.breakable_surfaces {
+                if let Some(node) = breakable.collider.filter(|&node| node >= node_parts.len()) {
+                    error!(
+                        "Breakable surface {} refers to collider node {}, but the model has {} \
+                         tagged nodes; it breaks off without a collider part",
+                        breakable.surface,
+                        node,
+                        node_parts.len()
+                    );
+                    breakable.collider = None;
+                }
+            }
+        }
+        entity_commands.insert((Collider::compound(parts), GltfColliderParts { node_parts }));
+    }
+}
+
+fn append_mesh(collider_mesh: &mut ColliderMesh, mesh: &Mesh, transform: &Transform) {
+    let positions = match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions,
+        _ => return,
+    };
+    let offset = collider_mesh.vertices.len() as u32;
+    collider_mesh.vertices.extend(
+        positions
+            .iter()
+            .map(|&position| transform.transform_point(Vec3::from_array(position))),
+    );
+
+    let indices = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect::<Vec<_>>(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    collider_mesh
+        .indices
+        .extend(indices.chunks_exact(3).map(|triangle| {
+            [
+                triangle[0] + offset,
+                triangle[1] + offset,
+                triangle[2] + offset,
+            ]
+        }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_points_and_skips_blank_lines() {
+        let points = ColliderPoints::parse("1;2;3\n\n -4.5 ; 0 ; 1e-1 \r\n").unwrap();
+        assert_eq!(
+            points.points,
+            vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(-4.5, 0.0, 0.1)]
+        );
+    }
+
+    #[test]
+    fn reports_an_invalid_number_with_its_line() {
+        match ColliderPoints::parse("1;2;3\n\n1;2,5;3\n") {
+            Err(ColliderLoadError::InvalidNumber { line, value }) => {
+                assert_eq!(line, 3);
+                assert_eq!(value, "2,5");
+            }
+            _ => panic!("expected an invalid number"),
+        }
+    }
+
+    #[test]
+    fn reports_the_wrong_value_count_with_its_line() {
+        match ColliderPoints::parse("1;2;3\n1;2\n") {
+            Err(ColliderLoadError::WrongValueCount { line, count }) => {
+                assert_eq!(line, 2);
+                assert_eq!(count, 2);
+            }
+            _ => panic!("expected a wrong value count"),
+        }
+        assert!(matches!(
+            ColliderPoints::parse("1;2;3;4"),
+            Err(ColliderLoadError::WrongValueCount { line: 1, count: 4 })
+        ));
+    }
+
+    #[test]
+    fn rejects_input_without_points() {
+        assert!(matches!(
+            ColliderPoints::parse(""),
+            Err(ColliderLoadError::NoPoints)
+        ));
+        assert!(matches!(
+            ColliderPoints::parse("\n  \n"),
+            Err(ColliderLoadError::NoPoints)
+        ));
+    }
+
+    #[test]
+    fn flat_points_fall_back_to_their_bounding_box() {
+        // All points in the plane y = 1, which has no convex hull.
+        let points = ColliderPoints::parse("0;1;0\n2;1;0\n2;1;4\n0;1;4\n1;1;2\n").unwrap();
+        let (position, collider) = points.collider(AxisConvention::default());
+        assert_eq!(position, Vec3::new(1.0, 1.0, 2.0));
+        // The flat side gets a minimum thickness.
+        let half_extents = collider.as_cuboid().unwrap().half_extents();
+        assert_eq!(half_extents, Vec3::new(1.0, 0.01, 2.0));
+
+        // With the axes converted, the box follows the airplane's coordinate system.
+        let axes = AxisConvention {
+            x: Axis::NegativeX,
+            y: Axis::PositiveY,
+            z: Axis::NegativeZ,
+        };
+        let (position, _) = points.collider(axes);
+        assert_eq!(position, Vec3::new(-1.0, 1.0, -2.0));
+
+        let points = ColliderPoints::parse("0;0;0\n1;0;0\n0;1;0\n0;0;1\n").unwrap();
+        let (position, collider) = points.collider(AxisConvention::default());
+        assert_eq!(position, Vec3::ZERO);
+        assert!(collider.as_cuboid().is_none());
+    }
+}
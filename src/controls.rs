@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::aerodynamics::update_control_surface_angle;
+use crate::input::{AirplaneAction, AirplaneControls};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FlapLever>()
+            .register_type::<Trim>()
+            .register_type::<ThrottleLever>()
+            .register_type::<Canopy>()
+            .add_system(update_flap_lever.before(update_control_surface_angle))
+            .add_system(update_trim.before(update_control_surface_angle))
+            .add_system(update_throttle_lever)
+            .add_system(update_canopy);
+    }
+}
+
+// Flap lever with discrete detents. The flaps move towards the angle of the selected detent
+// with a limited rate. Detent angles are the deflections of all flap surfaces, positive for
+// flaps down; the input sensitivity of flap surfaces is not used.
+#[derive(Reflect, Component, Clone)]
+pub struct FlapLever {
+    pub detents: Vec<f32>, // radians
+    pub detent: usize,
+    pub transition_rate: f32, // radians per second
+    pub angle: f32,           // radians
+}
+
+impl Default for FlapLever {
+    fn default() -> Self {
+        Self {
+            detents: vec![
+                0.0,
+                10.0f32.to_radians(),
+                20.0f32.to_radians(),
+                35.0f32.to_radians(),
+            ],
+            detent: 0,
+            transition_rate: 10.0f32.to_radians(),
+            angle: 0.0,
+        }
+    }
+}
+
+fn update_flap_lever(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut flap_lever_query: Query<&mut FlapLever>,
+    time: Res<Time>,
+) {
+    let action_state = input_query.single();
+
+    for mut flap_lever in &mut flap_lever_query {
+        if action_state.just_pressed(AirplaneAction::FlapsDown)
+            && flap_lever.detent + 1 < flap_lever.detents.len()
+        {
+            flap_lever.detent += 1;
+        }
+        if action_state.just_pressed(AirplaneAction::FlapsUp) {
+            flap_lever.detent = flap_lever.detent.saturating_sub(1);
+        }
+
+        let target_angle = flap_lever
+            .detents
+            .get(flap_lever.detent)
+            .copied()
+            .unwrap_or(0.0);
+        let max_step = flap_lever.transition_rate * time.delta_seconds();
+        flap_lever.angle += (target_angle - flap_lever.angle).clamp(-max_step, max_step);
+    }
+}
+
+// Throttle lever that stays where it was left. The throttle input moves it with a limited
+// rate, so that a self-centering stick or the keyboard can hold any setting.
+#[derive(Reflect, Component, Clone)]
+pub struct ThrottleLever {
+    pub position: f32, // 0 (idle) to 1 (full throttle)
+    pub rate: f32,     // per second at full input
+}
+
+impl Default for ThrottleLever {
+    fn default() -> Self {
+        Self {
+            position: 0.0,
+            rate: 0.5,
+        }
+    }
+}
+
+pub fn update_throttle_lever(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut throttle_lever_query: Query<&mut ThrottleLever>,
+    time: Res<Time>,
+) {
+    let action_state = input_query.single();
+    let throttle_input = action_state.clamped_value(AirplaneAction::Throttle);
+
+    for mut throttle_lever in &mut throttle_lever_query {
+        let step = throttle_input * throttle_lever.rate * time.delta_seconds();
+        throttle_lever.position = (throttle_lever.position + step).clamp(0.0, 1.0);
+    }
+}
+
+// Canopy that swings open or closed with a limited rate when toggled.
+#[derive(Reflect, Component, Clone)]
+pub struct Canopy {
+    pub open: bool,
+    pub opening: f32, // 0 (closed) to 1 (open)
+    pub rate: f32,    // per second
+}
+
+impl Default for Canopy {
+    fn default() -> Self {
+        Self {
+            open: false,
+            opening: 0.0,
+            rate: 0.5,
+        }
+    }
+}
+
+pub fn update_canopy(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut canopy_query: Query<&mut Canopy>,
+    time: Res<Time>,
+) {
+    let action_state = input_query.single();
+
+    for mut canopy in &mut canopy_query {
+        if action_state.just_pressed(AirplaneAction::Canopy) {
+            canopy.open = !canopy.open;
+        }
+        let target_opening = if canopy.open { 1.0 } else { 0.0 };
+        let max_step = canopy.rate * time.delta_seconds();
+        canopy.opening += (target_opening - canopy.opening).clamp(-max_step, max_step);
+    }
+}
+
+// Trim offsets added to the pitch and yaw inputs, in the same units as the stick input.
+#[derive(Reflect, Component, Clone)]
+pub struct Trim {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub rate: f32, // input units per second
+    // After trimming to the current stick position the stick input is ignored until the
+    // stick has been released, so that the deflection is not applied twice.
+    pub pitch_input_suppressed: bool,
+    pub yaw_input_suppressed: bool,
+}
+
+impl Default for Trim {
+    fn default() -> Self {
+        Self {
+            pitch: 0.0,
+            yaw: 0.0,
+            rate: 0.25,
+            pitch_input_suppressed: false,
+            yaw_input_suppressed: false,
+        }
+    }
+}
+
+impl Trim {
+    pub fn pitch_input(&self, stick_input: f32) -> f32 {
+        let stick_input = if self.pitch_input_suppressed {
+            0.0
+        } else {
+            stick_input
+        };
+        (stick_input + self.pitch).clamp(-1.0, 1.0)
+    }
+
+    pub fn yaw_input(&self, stick_input: f32) -> f32 {
+        let stick_input = if self.yaw_input_suppressed {
+            0.0
+        } else {
+            stick_input
+        };
+        (stick_input + self.yaw).clamp(-1.0, 1.0)
+    }
+}
+
+fn update_trim(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut trim_query: Query<&mut Trim>,
+    time: Res<Time>,
+) {
+    let action_state = input_query.single();
+    let pitch_input = action_state.clamped_value(AirplaneAction::Pitch);
+    let yaw_input = action_state.clamped_value(AirplaneAction::Yaw);
+    let released_threshold = 0.05;
+
+    for mut trim in &mut trim_query {
+        let step = trim.rate * time.delta_seconds();
+        // Pulling the stick back raises the nose.
+        if action_state.pressed(AirplaneAction::TrimNoseUp) {
+            trim.pitch -= step;
+        }
+        if action_state.pressed(AirplaneAction::TrimNoseDown) {
+            trim.pitch += step;
+        }
+        if action_state.pressed(AirplaneAction::TrimYawLeft) {
+            trim.yaw -= step;
+        }
+        if action_state.pressed(AirplaneAction::TrimYawRight) {
+            trim.yaw += step;
+        }
+
+        // Like the trigger trim of many gliders, the current stick position becomes the
+        // trimmed position, so that the airplane keeps its speed with the stick released.
+        if action_state.just_pressed(AirplaneAction::TrimToStick) {
+            trim.pitch = trim.pitch_input(pitch_input);
+            trim.yaw = trim.yaw_input(yaw_input);
+            trim.pitch_input_suppressed = true;
+            trim.yaw_input_suppressed = true;
+        }
+        if pitch_input.abs() < released_threshold {
+            trim.pitch_input_suppressed = false;
+        }
+        if yaw_input.abs() < released_threshold {
+            trim.yaw_input_suppressed = false;
+        }
+
+        trim.pitch = trim.pitch.clamp(-1.0, 1.0);
+        trim.yaw = trim.yaw.clamp(-1.0, 1.0);
+    }
+}
@@ -0,0 +1,566 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::aircraft::AircraftDefinition;
+use crate::controls::{Canopy, FlapLever, ThrottleLever, Trim};
+use crate::input::{AirplaneAction, AirplaneControls};
+use crate::landing_gear::{simulate_landing_gear, LandingGear};
+use crate::powerplant::{JetEngine, Powerplant};
+use crate::soaring::SoaringWeather;
+use crate::structure::Debris;
+use crate::turbulence::Turbulence;
+
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CrashDetection>()
+            .register_type::<Respawn>()
+            .add_event::<Touchdown>()
+            .add_event::<HardLanding>()
+            .add_event::<Crash>()
+            .add_event::<ResetAirplane>()
+            .add_system(enable_contact_force_events)
+            .add_system(detect_impacts.after(simulate_landing_gear))
+            .add_system(reset_on_input.before(respawn_airplanes))
+            .add_system(respawn_airplanes.after(detect_impacts));
+    }
+}
+
+// Time without ground contact after which the next contact counts as a new touchdown.
+const AIRBORNE_TIME: f32 = 0.5; // seconds
+
+// The impact energies are the kinetic energy of the airplane's velocity along the contact
+// force just before the impact, plus a fraction of the energy along the ground for contacts
+// of the airframe.
+#[derive(Clone, Copy, Debug)]
+pub struct Touchdown {
+    pub airplane: Entity,
+    pub impact_energy: f32, // J
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HardLanding {
+    pub airplane: Entity,
+    pub impact_energy: f32, // J
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Crash {
+    pub airplane: Entity,
+    pub impact_energy: f32, // J
+}
+
+// Resets the airplane to its `Respawn` state.
+#[derive(Clone, Copy, Debug)]
+pub struct ResetAirplane(pub Entity);
+
+#[derive(Reflect, Component, Clone)]
+pub struct CrashDetection {
+    pub hard_landing_energy: f32, // J
+    pub crash_energy: f32,        // J
+    // Fraction of the kinetic energy parallel to the ground that counts towards impacts of
+    // the airframe, so that flying into the ground at a shallow angle is a crash while a
+    // wing tip touching down at taxiing speed is not.
+    pub sliding_energy_fraction: f32,
+    // Contact forces below this don't produce contact force events.
+    pub contact_force_threshold: f32, // N
+    pub crashed: bool,
+    pub gear_contact: bool,
+    pub last_contact_time: f32, // seconds since startup
+    pub previous_velocity: Vec3,
+}
+
+impl Default for CrashDetection {
+    fn default() -> Self {
+        Self {
+            // Sinking at about 2 m/s and 5 m/s with a 600 kg glider
+            hard_landing_energy: 1200.0,
+            crash_energy: 7500.0,
+            // Crashes at about 60 km/h along the ground
+            sliding_energy_fraction: 0.1,
+            contact_force_threshold: 1000.0,
+            crashed: false,
+            gear_contact: false,
+            last_contact_time: f32::NEG_INFINITY,
+            previous_velocity: Vec3::ZERO,
+        }
+    }
+}
+
+impl CrashDetection {
+    pub fn reset(&self) -> Self {
+        Self {
+            hard_landing_energy: self.hard_landing_energy,
+            crash_energy: self.crash_energy,
+            sliding_energy_fraction: self.sliding_energy_fraction,
+            contact_force_threshold: self.contact_force_threshold,
+            ..default()
+        }
+    }
+}
+
+// State the airplane is reset to, after a crash or on request.
+#[derive(Reflect, Component, Clone)]
+pub struct Respawn {
+    pub transform: Transform,
+    pub velocity: Velocity,
+    // Delay between a crash and the automatic reset, `None` to stay crashed.
+    pub after_crash: Option<f32>, // seconds
+    pub countdown: Option<f32>,   // seconds
+}
+
+impl Respawn {
+    pub fn new(transform: Transform, velocity: Velocity) -> Self {
+        Self {
+            transform,
+            velocity,
+            after_crash: Some(3.0),
+            countdown: None,
+        }
+    }
+}
+
+fn enable_contact_force_events(
+    mut commands: Commands,
+    airplane_query: Query<(Entity, &CrashDetection), Added<CrashDetection>>,
+) {
+    for (entity, crash_detection) in &airplane_query {
+        commands.entity(entity).insert((
+            ActiveEvents::CONTACT_FORCE_EVENTS,
+            ContactForceEventThreshold(crash_detection.contact_force_threshold),
+        ));
+    }
+}
+
+pub fn detect_impacts(
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    mut airplane_query: Query<(
+        Entity,
+        &mut CrashDetection,
+        &Velocity,
+        &ReadMassProperties,
+        Option<&LandingGear>,
+    )>,
+    mut touchdown_events: EventWriter<Touchdown>,
+    mut hard_landing_events: EventWriter<HardLanding>,
+    mut crash_events: EventWriter<Crash>,
+    time: Res<Time>,
+) {
+    let contact_force_events: Vec<ContactForceEvent> =
+        contact_force_events.iter().copied().collect();
+    let now = time.elapsed_seconds();
+
+    for (entity, mut crash_detection, velocity, read_mass_properties, landing_gear) in
+        &mut airplane_query
+    {
+        let mass = read_mass_properties.0.mass;
+        let previous_velocity = crash_detection.previous_velocity;
+        crash_detection.previous_velocity = velocity.linvel;
+        let kinetic_energy =
+            |direction: Vec3| 0.5 * mass * previous_velocity.dot(direction).powi(2);
+
+        // Wheels and skids don't collide, they touch down when their struts get compressed.
+        let gear_contact = landing_gear.map_or(false, |landing_gear| {
+            landing_gear
+                .struts
+                .iter()
+                .any(|strut| strut.compression > 0.0)
+        });
+        let mut impact_energy = None;
+        if gear_contact && !crash_detection.gear_contact {
+            impact_energy = Some(kinetic_energy(Vec3::Y));
+        }
+        crash_detection.gear_contact = gear_contact;
+
+        for event in &contact_force_events {
+            if event.collider1 != entity && event.collider2 != entity {
+                continue;
+            }
+            let direction = event.max_force_direction.normalize_or_zero();
+            let energy = kinetic_energy(direction)
+                + crash_detection.sliding_energy_fraction
+                    * (0.5 * mass * previous_velocity.length_squared() - kinetic_energy(direction));
+            impact_energy =
+                Some(impact_energy.map_or(energy, |impact_energy: f32| impact_energy.max(energy)));
+        }
+
+        let impact_energy = match impact_energy {
+            Some(impact_energy) => impact_energy,
+            None => continue,
+        };
+        let airborne = now - crash_detection.last_contact_time > AIRBORNE_TIME;
+        crash_detection.last_contact_time = now;
+        if crash_detection.crashed {
+            continue;
+        }
+
+        if impact_energy >= crash_detection.crash_energy {
+            warn!("Crash with an impact energy of {:.0} J", impact_energy);
+            crash_detection.crashed = true;
+            crash_events.send(Crash {
+                airplane: entity,
+                impact_energy,
+            });
+        } else if impact_energy >= crash_detection.hard_landing_energy {
+            warn!(
+                "Hard landing with an impact energy of {:.0} J",
+                impact_energy
+            );
+            hard_landing_events.send(HardLanding {
+                airplane: entity,
+                impact_energy,
+            });
+        } else if airborne {
+            info!("Touchdown with an impact energy of {:.0} J", impact_energy);
+            touchdown_events.send(Touchdown {
+                airplane: entity,
+                impact_energy,
+            });
+        }
+    }
+}
+
+// Resets every airplane and the thermals, which are shared by all airplanes.
+fn reset_on_input(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    airplane_query: Query<Entity, With<Respawn>>,
+    mut reset_events: EventWriter<ResetAirplane>,
+    mut soaring_weather: ResMut<SoaringWeather>,
+) {
+    if input_query.single().just_pressed(AirplaneAction::Reset) {
+        for entity in &airplane_query {
+            reset_events.send(ResetAirplane(entity));
+        }
+        soaring_weather.reset();
+    }
+}
+
+// Resets the airplane, its engines and its turbulence, and removes the parts that broke off.
+// The thermals are left alone, so that the other airplanes keep flying in the same weather.
+fn respawn_airplanes(
+    mut commands: Commands,
+    mut crash_events: EventReader<Crash>,
+    mut reset_events: EventReader<ResetAirplane>,
+    mut airplane_query: Query<(
+        Entity,
+        &mut Respawn,
+        Option<&CrashDetection>,
+        Option<&Handle<AircraftDefinition>>,
+        Option<&Turbulence>,
+        Option<&mut Powerplant>,
+        Option<&mut JetEngine>,
+    )>,
+    debris_query: Query<(Entity, &Debris)>,
+    time: Res<Time>,
+) {
+    for crash in crash_events.iter() {
+        if let Ok((_, mut respawn, ..)) = airplane_query.get_mut(crash.airplane) {
+            respawn.countdown = respawn.after_crash;
+        }
+    }
+    for ResetAirplane(entity) in reset_events.iter() {
+        if let Ok((_, mut respawn, ..)) = airplane_query.get_mut(*entity) {
+            respawn.countdown = Some(0.0);
+        }
+    }
+
+    for (entity, mut respawn, crash_detection, definition, turbulence, powerplant, jet_engine) in
+        &mut airplane_query
+    {
+        let countdown = match respawn.countdown {
+            Some(countdown) => countdown - time.delta_seconds(),
+            None => continue,
+        };
+        if countdown > 0.0 {
+            respawn.countdown = Some(countdown);
+            continue;
+        }
+        respawn.countdown = None;
+
+        info!("Resetting airplane");
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert((
+            respawn.transform,
+            respawn.velocity,
+            ExternalForce::default(),
+            FlapLever::default(),
+            Trim::default(),
+            ThrottleLever::default(),
+            Canopy::default(),
+        ));
+        if let Some(crash_detection) = crash_detection {
+            entity_commands.insert(crash_detection.reset());
+        }
+        if let Some(turbulence) = turbulence {
+            entity_commands.insert(turbulence.reset());
+        }
+        if let Some(mut powerplant) = powerplant {
+            powerplant.reset();
+        }
+        if let Some(mut jet_engine) = jet_engine {
+            jet_engine.reset();
+        }
+        // Inserting the definition handle again reapplies the definition, which restores the
+        // surfaces, colliders, mass, ballast, fuel, landing gear and structure.
+        if let Some(definition) = definition {
+            entity_commands.insert(definition.clone());
+        }
+        for (debris_entity, debris) in &debris_query {
+            if debris.airplane == entity {
+                commands.entity(debris_entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::landing_gear::Strut;
+    use bevy::utils::{Duration, Instant};
+
+    #[derive(Debug, PartialEq)]
+    enum Impact {
+        None,
+        Touchdown,
+        HardLanding,
+        Crash,
+    }
+
+    fn insert_time(world: &mut World, seconds: f32) {
+        let start = Instant::now();
+        let mut time = Time::default();
+        time.update_with_instant(start);
+        time.update_with_instant(start + Duration::from_secs_f32(seconds));
+        world.insert_resource(time);
+    }
+
+    // Runs `detect_impacts` for a 600 kg glider that was flying at `previous_velocity`, with
+    // the landing gear touching the ground or the airframe hitting it along `contact`.
+    fn impact(
+        crash_detection: CrashDetection,
+        previous_velocity: Vec3,
+        gear_contact: bool,
+        contact: Option<Vec3>,
+    ) -> (Impact, f32, CrashDetection) {
+        let mut world = World::new();
+        world.init_resource::<Events<ContactForceEvent>>();
+        world.init_resource::<Events<Touchdown>>();
+        world.init_resource::<Events<HardLanding>>();
+        world.init_resource::<Events<Crash>>();
+        insert_time(&mut world, 1.0);
+        let airplane = world
+            .spawn((
+                CrashDetection {
+                    previous_velocity,
+                    ..crash_detection
+                },
+                Velocity::zero(),
+                ReadMassProperties(MassProperties {
+                    mass: 600.0,
+                    ..default()
+                }),
+                LandingGear {
+                    struts: vec![Strut {
+                        compression: if gear_contact { 0.1 } else { 0.0 },
+                        ..default()
+                    }],
+                    ..default()
+                },
+            ))
+            .id();
+        if let Some(direction) = contact {
+            let ground = world.spawn_empty().id();
+            world
+                .resource_mut::<Events<ContactForceEvent>>()
+                .send(ContactForceEvent {
+                    collider1: airplane,
+                    collider2: ground,
+                    total_force: direction * 50_000.0,
+                    total_force_magnitude: 50_000.0,
+                    max_force_direction: direction,
+                    max_force_magnitude: 50_000.0,
+                });
+        }
+
+        SystemStage::single(detect_impacts).run(&mut world);
+
+        let crash_detection = world.get::<CrashDetection>(airplane).unwrap().clone();
+        if let Some(crash) = world
+            .resource::<Events<Crash>>()
+            .iter_current_update_events()
+            .next()
+        {
+            return (Impact::Crash, crash.impact_energy, crash_detection);
+        }
+        if let Some(hard_landing) = world
+            .resource::<Events<HardLanding>>()
+            .iter_current_update_events()
+            .next()
+        {
+            return (
+                Impact::HardLanding,
+                hard_landing.impact_energy,
+                crash_detection,
+            );
+        }
+        if let Some(touchdown) = world
+            .resource::<Events<Touchdown>>()
+            .iter_current_update_events()
+            .next()
+        {
+            return (Impact::Touchdown, touchdown.impact_energy, crash_detection);
+        }
+        (Impact::None, 0.0, crash_detection)
+    }
+
+    #[test]
+    fn landing_gear_impacts_are_classified_by_the_sink_rate() {
+        let sinking = |sink_rate: f32| {
+            let (impact, energy, _) = impact(
+                CrashDetection::default(),
+                Vec3::new(0.0, -sink_rate, -25.0),
+                true,
+                None,
+            );
+            (impact, energy)
+        };
+
+        // Only the vertical velocity counts for the landing gear: 0.5 * 600 kg * (1.5 m/s)².
+        let (touchdown, energy) = sinking(1.5);
+        assert_eq!(touchdown, Impact::Touchdown);
+        assert!((energy - 675.0).abs() < 1e-2);
+        assert_eq!(sinking(2.5).0, Impact::HardLanding);
+        assert_eq!(sinking(6.0).0, Impact::Crash);
+
+        // The thresholds are inclusive: exactly 1200 J at 2 m/s and 7500 J at 5 m/s.
+        assert_eq!(sinking(2.0).0, Impact::HardLanding);
+        assert_eq!(sinking(5.0).0, Impact::Crash);
+        assert_eq!(sinking(0.0).0, Impact::Touchdown);
+    }
+
+    #[test]
+    fn touchdowns_need_the_airplane_to_be_airborne() {
+        // Bouncing back onto the gear 0.2 s after the last contact is not a new touchdown.
+        let bouncing = CrashDetection {
+            last_contact_time: 0.8,
+            ..default()
+        };
+        let (no_touchdown, _, crash_detection) =
+            impact(bouncing.clone(), Vec3::new(0.0, -1.0, -20.0), true, None);
+        assert_eq!(no_touchdown, Impact::None);
+        assert!(crash_detection.gear_contact);
+        assert!(crash_detection.last_contact_time > 0.9);
+
+        // A hard landing is reported even right after the last contact.
+        let (hard_landing, ..) = impact(bouncing, Vec3::new(0.0, -3.0, -20.0), true, None);
+        assert_eq!(hard_landing, Impact::HardLanding);
+
+        // Staying on the gear doesn't touch down again.
+        let rolling = CrashDetection {
+            gear_contact: true,
+            ..default()
+        };
+        let (rolling, ..) = impact(rolling, Vec3::new(0.0, -1.0, -20.0), true, None);
+        assert_eq!(rolling, Impact::None);
+    }
+
+    #[test]
+    fn airframe_impacts_count_part_of_the_sliding_energy() {
+        let belly_landing = |speed: f32| {
+            impact(
+                CrashDetection::default(),
+                Vec3::new(0.0, 0.0, -speed),
+                false,
+                Some(Vec3::Y),
+            )
+        };
+
+        // 10 % of 0.5 * 600 kg * (15 m/s)² = 6750 J, a hard landing at 54 km/h.
+        let (hard_landing, energy, crash_detection) = belly_landing(15.0);
+        assert_eq!(hard_landing, Impact::HardLanding);
+        assert!((energy - 6750.0).abs() < 1e-1);
+        assert!(!crash_detection.crashed);
+
+        // 12000 J at 72 km/h is a crash.
+        let (crash, energy, crash_detection) = belly_landing(20.0);
+        assert_eq!(crash, Impact::Crash);
+        assert!((energy - 12_000.0).abs() < 1e-1);
+        assert!(crash_detection.crashed);
+
+        // Flying into the ground counts in full.
+        let (crash, ..) = impact(
+            CrashDetection::default(),
+            Vec3::new(0.0, -6.0, -20.0),
+            false,
+            Some(Vec3::Y),
+        );
+        assert_eq!(crash, Impact::Crash);
+    }
+
+    #[test]
+    fn crashed_airplanes_report_no_further_impacts() {
+        let crashed = CrashDetection {
+            crashed: true,
+            ..default()
+        };
+        let (impact, ..) = impact(crashed, Vec3::new(0.0, -10.0, 0.0), true, Some(Vec3::Y));
+        assert_eq!(impact, Impact::None);
+    }
+
+    #[test]
+    fn respawn_restores_the_airplane_after_a_crash() {
+        let mut world = World::new();
+        world.init_resource::<Events<Crash>>();
+        world.init_resource::<Events<ResetAirplane>>();
+        let start = Transform::from_xyz(0.0, 500.0, 0.0);
+        let start_velocity = Velocity::linear(Vec3::new(0.0, 0.0, -25.0));
+        let crash_detection = CrashDetection {
+            crash_energy: 9000.0,
+            ..default()
+        };
+        let airplane = world
+            .spawn((
+                Respawn::new(start, start_velocity),
+                Transform::from_xyz(100.0, 0.0, -800.0),
+                Velocity::zero(),
+                CrashDetection {
+                    crashed: true,
+                    gear_contact: true,
+                    last_contact_time: 60.0,
+                    previous_velocity: Vec3::new(0.0, -8.0, -30.0),
+                    ..crash_detection.clone()
+                },
+            ))
+            .id();
+        world.resource_mut::<Events<Crash>>().send(Crash {
+            airplane,
+            impact_energy: 20_000.0,
+        });
+        let mut stage = SystemStage::single(respawn_airplanes);
+
+        // The airplane stays on the ground for 3 s after the crash.
+        insert_time(&mut world, 1.0);
+        stage.run(&mut world);
+        assert_eq!(world.get::<Transform>(airplane).unwrap().translation.y, 0.0);
+        assert!(world.get::<CrashDetection>(airplane).unwrap().crashed);
+        let countdown = world.get::<Respawn>(airplane).unwrap().countdown.unwrap();
+        assert!((countdown - 2.0).abs() < 1e-3);
+
+        insert_time(&mut world, 2.5);
+        stage.run(&mut world);
+        let entity = world.entity(airplane);
+        assert_eq!(*entity.get::<Transform>().unwrap(), start);
+        assert_eq!(*entity.get::<Velocity>().unwrap(), start_velocity);
+        assert!(entity.get::<Respawn>().unwrap().countdown.is_none());
+        let restored = entity.get::<CrashDetection>().unwrap();
+        assert!(!restored.crashed);
+        assert!(!restored.gear_contact);
+        assert_eq!(restored.last_contact_time, f32::NEG_INFINITY);
+        assert_eq!(restored.previous_velocity, Vec3::ZERO);
+        // The configured thresholds are kept.
+        assert_eq!(restored.crash_energy, 9000.0);
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::powerplant::{simulate_jet_engines, simulate_powerplants, JetEngine, Powerplant};
+
+pub struct FuelPlugin;
+
+impl Plugin for FuelPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FuelSystem>().add_system(
+            consume_fuel
+                .after(simulate_powerplants)
+                .after(simulate_jet_engines),
+        );
+    }
+}
+
+#[derive(Reflect, FromReflect, Deserialize, Clone)]
+#[serde(default)]
+pub struct FuelTank {
+    pub name: String,
+    pub capacity: f32,  // kg
+    pub quantity: f32,  // kg
+    pub position: Vec3, // relative to the airplane
+    // Tanks with a closed selector valve don't feed the engines.
+    pub selected: bool,
+}
+
+impl Default for FuelTank {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            capacity: 100.0,
+            quantity: 100.0,
+            position: Vec3::ZERO,
+            selected: true,
+        }
+    }
+}
+
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
+pub enum FuelFeed {
+    // The selected tanks are emptied one after another, in the order of the list.
+    #[default]
+    Sequential,
+    // The selected tanks are drained in proportion to their content and run dry together.
+    Balanced,
+}
+
+// Fuel tanks feeding all engines of the airplane. When the selected tanks can't deliver the
+// fuel flow demanded by the engines, the engines stop.
+#[derive(Reflect, Component, Default, Clone)]
+pub struct FuelSystem {
+    pub tanks: Vec<FuelTank>,
+    pub feed: FuelFeed,
+}
+
+impl FuelSystem {
+    pub fn quantity(&self) -> f32 {
+        self.tanks.iter().map(|tank| tank.quantity).sum()
+    }
+
+    // Removes up to `amount` kg of fuel from the selected tanks and returns the amount
+    // delivered.
+    pub fn draw(&mut self, amount: f32) -> f32 {
+        let selected_tanks = self.tanks.iter_mut().filter(|tank| tank.selected);
+        let mut remaining = amount;
+        match self.feed {
+            FuelFeed::Sequential => {
+                for tank in selected_tanks {
+                    let drawn = remaining.min(tank.quantity);
+                    tank.quantity -= drawn;
+                    remaining -= drawn;
+                }
+            }
+            FuelFeed::Balanced => {
+                let selected_tanks: Vec<&mut FuelTank> = selected_tanks.collect();
+                let available: f32 = selected_tanks.iter().map(|tank| tank.quantity).sum();
+                if available > 0.0 {
+                    let fraction = (amount / available).min(1.0);
+                    for tank in selected_tanks {
+                        let drawn = tank.quantity * fraction;
+                        tank.quantity -= drawn;
+                        remaining -= drawn;
+                    }
+                }
+            }
+        }
+        amount - remaining.max(0.0)
+    }
+}
+
+pub fn consume_fuel(
+    mut airplane_query: Query<(
+        &mut FuelSystem,
+        Option<&mut Powerplant>,
+        Option<&mut JetEngine>,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut fuel_system, powerplant, jet_engine) in &mut airplane_query {
+        let fuel_flow = powerplant
+            .as_ref()
+            .map_or(0.0, |powerplant| powerplant.fuel_flow)
+            + jet_engine
+                .as_ref()
+                .map_or(0.0, |jet_engine| jet_engine.fuel_flow);
+        let demand = fuel_flow * time.delta_seconds();
+        if demand <= 0.0 {
+            continue;
+        }
+
+        let delivered = fuel_system.draw(demand);
+        if delivered < demand * 0.999 {
+            // fuel starvation
+            if let Some(mut powerplant) = powerplant {
+                powerplant.running = false;
+            }
+            if let Some(mut jet_engine) = jet_engine {
+                jet_engine.running = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::{Duration, Instant};
+
+    fn tank(name: &str, quantity: f32, selected: bool) -> FuelTank {
+        FuelTank {
+            name: name.to_string(),
+            capacity: 50.0,
+            quantity,
+            selected,
+            ..default()
+        }
+    }
+
+    fn quantities(fuel_system: &FuelSystem) -> Vec<f32> {
+        fuel_system.tanks.iter().map(|tank| tank.quantity).collect()
+    }
+
+    #[test]
+    fn sequential_feed_empties_the_selected_tanks_in_order() {
+        let mut fuel_system = FuelSystem {
+            tanks: vec![
+                tank("Left", 10.0, true),
+                tank("Right", 20.0, true),
+                tank("Reserve", 5.0, false),
+            ],
+            feed: FuelFeed::Sequential,
+        };
+
+        assert_eq!(fuel_system.draw(4.0), 4.0);
+        assert_eq!(quantities(&fuel_system), vec![6.0, 20.0, 5.0]);
+        assert_eq!(fuel_system.draw(10.0), 10.0);
+        assert_eq!(quantities(&fuel_system), vec![0.0, 16.0, 5.0]);
+        // The closed reserve tank isn't touched when the others run dry.
+        assert_eq!(fuel_system.draw(20.0), 16.0);
+        assert_eq!(quantities(&fuel_system), vec![0.0, 0.0, 5.0]);
+        assert_eq!(fuel_system.quantity(), 5.0);
+    }
+
+    #[test]
+    fn balanced_feed_drains_the_selected_tanks_in_proportion() {
+        let mut fuel_system = FuelSystem {
+            tanks: vec![
+                tank("Left", 10.0, true),
+                tank("Right", 30.0, true),
+                tank("Reserve", 5.0, false),
+            ],
+            feed: FuelFeed::Balanced,
+        };
+
+        assert_eq!(fuel_system.draw(8.0), 8.0);
+        assert_eq!(quantities(&fuel_system), vec![8.0, 24.0, 5.0]);
+        // Both selected tanks run dry together.
+        assert_eq!(fuel_system.draw(50.0), 32.0);
+        assert_eq!(quantities(&fuel_system), vec![0.0, 0.0, 5.0]);
+        assert_eq!(fuel_system.draw(1.0), 0.0);
+    }
+
+    fn run_consume_fuel(world: &mut World, seconds: f32) {
+        let start = Instant::now();
+        let mut time = Time::default();
+        time.update_with_instant(start);
+        time.update_with_instant(start + Duration::from_secs_f32(seconds));
+        world.insert_resource(time);
+        SystemStage::single(consume_fuel).run(world);
+    }
+
+    #[test]
+    fn starvation_stops_the_engines() {
+        let mut world = World::new();
+        let airplane = world
+            .spawn((
+                FuelSystem {
+                    tanks: vec![tank("Main", 1.5, true)],
+                    feed: FuelFeed::Sequential,
+                },
+                Powerplant {
+                    fuel_flow: 0.5, // kg/s
+                    ..default()
+                },
+                JetEngine {
+                    fuel_flow: 0.5, // kg/s
+                    ..default()
+                },
+            ))
+            .id();
+
+        // One second at 1 kg/s leaves 0.5 kg, the engines keep running.
+        run_consume_fuel(&mut world, 1.0);
+        let entity = world.entity(airplane);
+        assert!((entity.get::<FuelSystem>().unwrap().quantity() - 0.5).abs() < 1e-4);
+        assert!(entity.get::<Powerplant>().unwrap().running);
+        assert!(entity.get::<JetEngine>().unwrap().running);
+
+        run_consume_fuel(&mut world, 1.0);
+        let entity = world.entity(airplane);
+        assert_eq!(entity.get::<FuelSystem>().unwrap().quantity(), 0.0);
+        assert!(!entity.get::<Powerplant>().unwrap().running);
+        assert!(!entity.get::<JetEngine>().unwrap().running);
+    }
+}
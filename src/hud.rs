@@ -17,7 +17,9 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands
         .spawn(NodeBundle {
             style: Style {
-                size: Size::new(Val::Auto, Val::Px(70.0)),
+                size: Size::new(Val::Auto, Val::Auto),
+                // Entries that don't fit into the window continue on the next line.
+                flex_wrap: FlexWrap::Wrap,
                 padding: UiRect {
                     left: Val::Px(80.0),
                     right: Val::Px(0.0),
@@ -31,60 +33,49 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         })
         .with_children(|parent| {
-            parent.spawn((
-                {
-                    TextBundle::from_section(
-                        "0 km/h",
-                        TextStyle {
-                            font: asset_server.load("fonts/RobotoCondensed-Light.ttf"),
-                            font_size: 70.0,
-                            color: Color::WHITE,
-                        },
-                    )
-                    .with_style(Style {
-                        size: Size::new(Val::Px(350.0), Val::Auto),
-                        ..default()
-                    })
-                },
-                MetricText(FlightMetric::Airspeed),
-            ));
-            parent.spawn((
-                {
-                    TextBundle::from_section(
-                        "0.00 m/s",
-                        TextStyle {
-                            font: asset_server.load("fonts/RobotoCondensed-Light.ttf"),
-                            font_size: 70.0,
-                            color: Color::WHITE,
-                        },
-                    )
-                    .with_style(Style {
-                        size: Size::new(Val::Px(350.0), Val::Auto),
-                        ..default()
-                    })
-                },
-                MetricText(FlightMetric::VerticalSpeed),
-            ));
-            parent.spawn((
-                {
-                    TextBundle::from_section(
-                        "0 m",
-                        TextStyle {
-                            font: asset_server.load("fonts/RobotoCondensed-Light.ttf"),
-                            font_size: 70.0,
-                            color: Color::WHITE,
-                        },
-                    )
-                    .with_style(Style {
-                        size: Size::new(Val::Px(350.0), Val::Auto),
-                        ..default()
-                    })
-                },
-                MetricText(FlightMetric::Height),
-            ));
+            let font = asset_server.load("fonts/RobotoCondensed-Light.ttf");
+            // Initial text and width (px) of each entry.
+            let entries = [
+                (FlightMetric::Airspeed, "0 km/h", 200.0),
+                (FlightMetric::VerticalSpeed, "0.00 m/s", 200.0),
+                (FlightMetric::Height, "0 m", 200.0),
+                (FlightMetric::Flaps, "Flaps 0°", 200.0),
+                (FlightMetric::PitchTrim, "Trim +0%", 170.0),
+                (FlightMetric::YawTrim, "Trim R 0%", 170.0),
+                (FlightMetric::Fuel, "Fuel 0 kg", 170.0),
+                (FlightMetric::LoadFactor, "1.0 g", 120.0),
+                (FlightMetric::GMeterMax, "Max +1.0 g", 150.0),
+                (FlightMetric::GMeterMin, "Min +1.0 g", 150.0),
+            ];
+            for (metric, text, width) in entries {
+                parent.spawn(metric_text(metric, text, width, font.clone()));
+            }
         });
 }
 
+fn metric_text(
+    metric: FlightMetric,
+    text: &str,
+    width: f32,
+    font: Handle<Font>,
+) -> (TextBundle, MetricText) {
+    (
+        TextBundle::from_section(
+            text,
+            TextStyle {
+                font,
+                font_size: 40.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            size: Size::new(Val::Px(width), Val::Auto),
+            ..default()
+        }),
+        MetricText(metric),
+    )
+}
+
 fn update_hud(
     airplane_query: Query<&FlightMetrics, With<Airplane>>,
     mut metric_query: Query<(&mut Text, &MetricText)>,
@@ -96,6 +87,18 @@ fn update_hud(
             FlightMetric::Airspeed => format!("{:.0} km/h", metrics.metrics[metric.0] * 3.6),
             FlightMetric::VerticalSpeed => format!("{:.2} m/s", metrics.metrics[metric.0]),
             FlightMetric::Height => format!("{:.0} m", metrics.metrics[metric.0]),
+            FlightMetric::Flaps => format!("Flaps {:.0}°", metrics.metrics[metric.0]),
+            // Pitch trim is shown positive for nose up, i.e. stick pulled back.
+            FlightMetric::PitchTrim => format!("Trim {:+.0}%", -metrics.metrics[metric.0] * 100.0),
+            FlightMetric::YawTrim => {
+                let trim = metrics.metrics[metric.0];
+                let side = if trim < 0.0 { "L" } else { "R" };
+                format!("Trim {} {:.0}%", side, trim.abs() * 100.0)
+            }
+            FlightMetric::Fuel => format!("Fuel {:.0} kg", metrics.metrics[metric.0]),
+            FlightMetric::LoadFactor => format!("{:.1} g", metrics.metrics[metric.0]),
+            FlightMetric::GMeterMax => format!("Max {:+.1} g", metrics.metrics[metric.0]),
+            FlightMetric::GMeterMin => format!("Min {:+.1} g", metrics.metrics[metric.0]),
         };
         text.sections[0].value = value;
     }
@@ -13,9 +13,24 @@ impl Plugin for InputPlugin {
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum AirplaneAction {
     Throttle,
+    Starter,
     Roll,
     Pitch,
     Yaw,
+    FlapsUp,
+    FlapsDown,
+    Airbrake,
+    TrimNoseUp,
+    TrimNoseDown,
+    TrimYawLeft,
+    TrimYawRight,
+    TrimToStick,
+    DumpBallast,
+    Brake,
+    ParkingBrake,
+    Reset,
+    ResetGMeter,
+    Canopy,
     CameraPanTilt,
 }
 
@@ -36,8 +51,41 @@ fn setup_input(mut commands: Commands) {
             SingleAxis::symmetric(GamepadAxisType::LeftStickX, 0.1),
             AirplaneAction::Yaw,
         ),
+        (
+            SingleAxis::symmetric(GamepadAxisType::LeftStickY, 0.1),
+            AirplaneAction::Airbrake,
+        ),
+        // The Z axis of a throttle slider or wheel, unused on common gamepads.
+        (
+            SingleAxis::symmetric(GamepadAxisType::RightZ, 0.1),
+            AirplaneAction::Throttle,
+        ),
     ]);
     input_map.insert(VirtualDPad::dpad(), AirplaneAction::CameraPanTilt);
+    input_map.insert(GamepadButtonType::LeftTrigger, AirplaneAction::FlapsUp);
+    input_map.insert(GamepadButtonType::RightTrigger, AirplaneAction::FlapsDown);
+    input_map.insert(GamepadButtonType::South, AirplaneAction::TrimNoseUp);
+    input_map.insert(GamepadButtonType::North, AirplaneAction::TrimNoseDown);
+    input_map.insert(GamepadButtonType::West, AirplaneAction::TrimYawLeft);
+    input_map.insert(GamepadButtonType::East, AirplaneAction::TrimYawRight);
+    input_map.insert(GamepadButtonType::RightThumb, AirplaneAction::TrimToStick);
+    input_map.insert(GamepadButtonType::Select, AirplaneAction::DumpBallast);
+    input_map.insert(GamepadButtonType::LeftTrigger2, AirplaneAction::Brake);
+    input_map.insert(GamepadButtonType::RightTrigger2, AirplaneAction::Brake);
+    input_map.insert(GamepadButtonType::Start, AirplaneAction::ParkingBrake);
+    input_map.insert(GamepadButtonType::LeftThumb, AirplaneAction::Reset);
+    // All gamepad buttons are taken, the starter, the G-meter reset and the canopy are on the
+    // keyboard.
+    input_map.insert(
+        VirtualAxis {
+            negative: KeyCode::PageDown.into(),
+            positive: KeyCode::PageUp.into(),
+        },
+        AirplaneAction::Throttle,
+    );
+    input_map.insert(KeyCode::S, AirplaneAction::Starter);
+    input_map.insert(KeyCode::G, AirplaneAction::ResetGMeter);
+    input_map.insert(KeyCode::C, AirplaneAction::Canopy);
 
     commands.spawn((
         InputManagerBundle::<AirplaneAction> {
@@ -0,0 +1,354 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::aerodynamics::simulate_aerodynamics;
+use crate::controls::Trim;
+use crate::input::{AirplaneAction, AirplaneControls};
+
+pub struct LandingGearPlugin;
+
+impl Plugin for LandingGearPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LandingGear>()
+            .add_system(update_ground_controls.before(simulate_landing_gear))
+            .add_system(simulate_landing_gear.after(simulate_aerodynamics));
+    }
+}
+
+// Velocity below which friction forces are scaled down linearly, so that the airplane comes
+// to rest instead of jittering around zero velocity.
+const FRICTION_VELOCITY: f32 = 0.5; // m/s
+
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
+pub enum ContactKind {
+    // A wheel rolls freely in the airplane's longitudinal direction and grips sideways.
+    #[default]
+    Wheel,
+    // A skid slides in every direction.
+    Skid,
+}
+
+// Spring-damper strut pointing down from its attachment point (along the airplane's local
+// -y axis). The ground is detected by a ray cast of the strut's length.
+#[derive(Reflect, FromReflect, Deserialize, Clone)]
+#[serde(default)]
+pub struct Strut {
+    pub name: String,
+    pub position: Vec3, // attachment point relative to the airplane
+    pub length: f32,    // meters, fully extended, including the wheel radius
+    pub stiffness: f32, // N/m
+    pub damping: f32,   // N·s/m
+    pub kind: ContactKind,
+    // Friction coefficient sideways for wheels, in every direction for skids.
+    pub friction_coefficient: f32,
+    pub rolling_resistance: f32,
+    // Friction coefficient of the fully applied wheel brake.
+    pub brake_coefficient: f32,
+    // Wheel deflection to the right at full right yaw input, zero for wheels that can't be
+    // steered. Wheels behind the center of mass, like a steerable tail wheel, need a negative
+    // angle to turn the airplane to the right.
+    pub steering_angle: f32, // radians
+    #[serde(skip)]
+    pub compression: f32, // meters
+    #[serde(skip)]
+    pub force: Vec3, // N, world space
+}
+
+impl Default for Strut {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            position: Vec3::ZERO,
+            length: 0.5,
+            stiffness: 50_000.0,
+            damping: 5_000.0,
+            kind: ContactKind::Wheel,
+            friction_coefficient: 0.7,
+            rolling_resistance: 0.02,
+            brake_coefficient: 0.0,
+            steering_angle: 0.0,
+            compression: 0.0,
+            force: Vec3::ZERO,
+        }
+    }
+}
+
+impl Strut {
+    // Ground contact force in world space. `ground_contact` is the distance from the
+    // attachment point to the ground along the strut, if within the strut's length, and the
+    // ground's surface normal there.
+    pub fn contact_force(
+        &mut self,
+        ground_contact: Option<(f32, Vec3)>,
+        point_velocity: Vec3,
+        forward: Vec3,
+        brake: f32,
+        delta_seconds: f32,
+    ) -> Vec3 {
+        let compression = ground_contact.map_or(0.0, |(distance, _)| self.length - distance);
+        let compression_rate = if delta_seconds > 0.0 {
+            (compression - self.compression) / delta_seconds
+        } else {
+            0.0
+        };
+        self.compression = compression;
+        let normal = match ground_contact {
+            Some((_, normal)) => normal,
+            None => {
+                self.force = Vec3::ZERO;
+                return self.force;
+            }
+        };
+
+        // The damper only pushes, the ground can't pull the airplane down.
+        let normal_force =
+            (self.stiffness * compression + self.damping * compression_rate).max(0.0);
+
+        let sliding_velocity = point_velocity - normal * point_velocity.dot(normal);
+        let friction = match self.kind {
+            ContactKind::Wheel => {
+                let forward = (forward - normal * forward.dot(normal)).normalize_or_zero();
+                let right = forward.cross(normal);
+                let longitudinal_coefficient =
+                    self.rolling_resistance + self.brake_coefficient * brake;
+                -forward
+                    * friction_scale(sliding_velocity.dot(forward))
+                    * longitudinal_coefficient
+                    * normal_force
+                    - right
+                        * friction_scale(sliding_velocity.dot(right))
+                        * self.friction_coefficient
+                        * normal_force
+            }
+            ContactKind::Skid => {
+                -(sliding_velocity / FRICTION_VELOCITY).clamp_length_max(1.0)
+                    * self.friction_coefficient
+                    * normal_force
+            }
+        };
+
+        self.force = normal * normal_force + friction;
+        self.force
+    }
+}
+
+fn friction_scale(velocity: f32) -> f32 {
+    (velocity / FRICTION_VELOCITY).clamp(-1.0, 1.0)
+}
+
+#[derive(Reflect, Component, Default, Clone)]
+pub struct LandingGear {
+    pub struts: Vec<Strut>,
+    pub brake: f32,    // 0 to 1
+    pub steering: f32, // -1 (left) to 1 (right), follows the yaw input
+    pub parking_brake: bool,
+}
+
+impl LandingGear {
+    // Brake applied to a wheel at the given lateral position. With the yaw input the brakes
+    // on the side of the turn are applied harder than on the other side.
+    pub fn wheel_brake(&self, lateral_position: f32) -> f32 {
+        if self.parking_brake {
+            return 1.0;
+        }
+        if lateral_position.abs() < 0.01 {
+            return self.brake;
+        }
+        (self.brake * (1.0 + self.steering * lateral_position.signum())).clamp(0.0, 1.0)
+    }
+
+    // Direction the wheel rolls in, relative to the airplane.
+    pub fn wheel_direction(&self, strut: &Strut) -> Vec3 {
+        Quat::from_rotation_y(-strut.steering_angle * self.steering) * Vec3::NEG_Z
+    }
+}
+
+fn update_ground_controls(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut landing_gear_query: Query<(&mut LandingGear, Option<&Trim>)>,
+) {
+    let action_state = input_query.single();
+    let brake = if action_state.pressed(AirplaneAction::Brake) {
+        1.0
+    } else {
+        0.0
+    };
+    let yaw_input = action_state.clamped_value(AirplaneAction::Yaw);
+
+    for (mut landing_gear, trim) in &mut landing_gear_query {
+        landing_gear.brake = brake;
+        landing_gear.steering = match trim {
+            Some(trim) => trim.yaw_input(yaw_input),
+            None => yaw_input,
+        }
+        .clamp(-1.0, 1.0);
+        if action_state.just_pressed(AirplaneAction::ParkingBrake) {
+            landing_gear.parking_brake = !landing_gear.parking_brake;
+        }
+    }
+}
+
+pub fn simulate_landing_gear(
+    mut airplane_query: Query<(
+        &mut LandingGear,
+        &mut ExternalForce,
+        &ReadMassProperties,
+        &Transform,
+        &Velocity,
+    )>,
+    rapier_context: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    for (mut landing_gear, mut external_force, read_mass_properties, transform, velocity) in
+        &mut airplane_query
+    {
+        let world_center_of_mass =
+            transform.transform_point(read_mass_properties.0.local_center_of_mass);
+        let down = transform.down();
+
+        for index in 0..landing_gear.struts.len() {
+            let forward =
+                transform.rotation * landing_gear.wheel_direction(&landing_gear.struts[index]);
+            let brake = landing_gear.wheel_brake(landing_gear.struts[index].position.x);
+            let strut = &mut landing_gear.struts[index];
+            let attachment_point = transform.transform_point(strut.position);
+            // The airplane itself is a dynamic body and not hit by the ray. A ray starting
+            // inside the ground has no normal, the ground is taken as level then.
+            let ground_contact = rapier_context
+                .cast_ray_and_get_normal(
+                    attachment_point,
+                    down,
+                    strut.length,
+                    true,
+                    QueryFilter::only_fixed(),
+                )
+                .map(|(_, intersection)| {
+                    (
+                        intersection.toi,
+                        intersection.normal.try_normalize().unwrap_or(Vec3::Y),
+                    )
+                });
+
+            let contact_point = attachment_point
+                + down * ground_contact.map_or(strut.length, |(distance, _)| distance);
+            let relative_position = contact_point - world_center_of_mass;
+            let point_velocity = velocity.linvel + velocity.angvel.cross(relative_position);
+
+            let force = strut.contact_force(
+                ground_contact,
+                point_velocity,
+                forward,
+                brake,
+                time.delta_seconds(),
+            );
+            external_force.force += force;
+            external_force.torque += relative_position.cross(force);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_eq(actual: Vec3, expected: Vec3) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-2),
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    fn strut(kind: ContactKind) -> Strut {
+        Strut {
+            length: 0.5,
+            stiffness: 50_000.0,
+            damping: 5_000.0,
+            kind,
+            friction_coefficient: 0.7,
+            rolling_resistance: 0.02,
+            brake_coefficient: 0.5,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn spring_and_damper_follow_compression_and_rate() {
+        let mut strut = strut(ContactKind::Wheel);
+        strut.compression = 0.1;
+        // 0.12 m compressed, compressing at 2 m/s
+        let force = strut.contact_force(Some((0.38, Vec3::Y)), Vec3::ZERO, Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(force, Vec3::Y * (50_000.0 * 0.12 + 5_000.0 * 2.0));
+        assert!((strut.compression - 0.12).abs() < 1e-6);
+
+        // Extending faster than the spring pushes, the damper must not pull.
+        let force = strut.contact_force(Some((0.48, Vec3::Y)), Vec3::ZERO, Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(force, Vec3::ZERO);
+    }
+
+    #[test]
+    fn pushes_along_the_ground_normal() {
+        let mut strut = strut(ContactKind::Wheel);
+        strut.compression = 0.1;
+        let normal = Vec3::new(0.0, 1.0, 1.0).normalize();
+        let force = strut.contact_force(Some((0.4, normal)), Vec3::ZERO, Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(force, normal * 50_000.0 * 0.1);
+    }
+
+    #[test]
+    fn friction_is_limited_by_the_coefficients() {
+        let normal_force = 50_000.0 * 0.1;
+        let contact = Some((0.4, Vec3::Y));
+
+        // A wheel grips sideways and rolls forward.
+        let mut wheel = strut(ContactKind::Wheel);
+        wheel.compression = 0.1;
+        let force = wheel.contact_force(contact, Vec3::new(5.0, 0.0, 0.0), Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(force, Vec3::new(-0.7 * normal_force, normal_force, 0.0));
+        let force = wheel.contact_force(contact, Vec3::new(0.0, 0.0, -5.0), Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(force, Vec3::new(0.0, normal_force, 0.02 * normal_force));
+        // The brake adds to the rolling resistance.
+        let force = wheel.contact_force(contact, Vec3::new(0.0, 0.0, -5.0), Vec3::NEG_Z, 1.0, 0.01);
+        assert_vec_eq(force, Vec3::new(0.0, normal_force, 0.52 * normal_force));
+
+        // A skid slides against the velocity in every direction.
+        let mut skid = strut(ContactKind::Skid);
+        skid.compression = 0.1;
+        let velocity = Vec3::new(3.0, 0.0, 4.0);
+        let force = skid.contact_force(contact, velocity, Vec3::NEG_Z, 0.0, 0.01);
+        assert_vec_eq(
+            force,
+            Vec3::Y * normal_force - velocity.normalize() * 0.7 * normal_force,
+        );
+    }
+
+    #[test]
+    fn tail_wheel_steers_the_other_way() {
+        let landing_gear = LandingGear {
+            steering: 1.0,
+            ..default()
+        };
+        let nose_wheel = Strut {
+            steering_angle: 0.3,
+            ..default()
+        };
+        let tail_wheel = Strut {
+            steering_angle: -0.3,
+            ..default()
+        };
+        assert!(landing_gear.wheel_direction(&nose_wheel).x > 0.0);
+        assert!(landing_gear.wheel_direction(&tail_wheel).x < 0.0);
+    }
+
+    #[test]
+    fn no_force_without_ground_contact() {
+        let mut strut = strut(ContactKind::Wheel);
+        strut.compression = 0.1;
+        let force = strut.contact_force(None, Vec3::new(1.0, -2.0, -30.0), Vec3::NEG_Z, 1.0, 0.01);
+        assert_vec_eq(force, Vec3::ZERO);
+        assert_eq!(strut.compression, 0.0);
+    }
+}
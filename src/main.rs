@@ -1,39 +1,87 @@
+use bevy::asset::AssetPlugin;
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_prototype_debug_lines::*;
 use bevy_rapier3d::prelude::*;
 
-use std::f32::consts::PI;
-
 #[macro_use]
 extern crate enum_map;
 
 mod aerodynamics;
+mod aircraft;
 mod airplane;
+mod atmosphere;
+mod ballast;
 mod camera;
+mod collider;
+mod controls;
+mod crash;
+mod fuel;
 mod hud;
 mod input;
+mod landing_gear;
+mod mass;
+mod model;
+mod powerplant;
+mod random;
+mod soaring;
+mod structure;
+mod turbulence;
+mod wind;
 
-use aerodynamics::{
-    AeroSurface, AeroSurfaceConfig, AeroSurfaceList, AerodynamicsPlugin, ControlInputType,
-};
+use aerodynamics::AerodynamicsPlugin;
+use aircraft::{AircraftDefinition, AircraftPlugin};
 use airplane::{Airplane, AirplaneBundle, AirplanePlugin};
+use atmosphere::AtmospherePlugin;
+use ballast::BallastPlugin;
 use camera::CameraPlugin;
+use collider::ColliderPlugin;
+use controls::ControlsPlugin;
+use crash::{CrashDetection, CrashPlugin, Respawn};
+use fuel::FuelPlugin;
 use hud::AirplaneHudPlugin;
 use input::InputPlugin;
+use landing_gear::LandingGearPlugin;
+use mass::MassPlugin;
+use model::ModelPlugin;
+use powerplant::PowerplantPlugin;
+use soaring::{Ridge, SoaringPlugin, SoaringWeather};
+use structure::StructurePlugin;
+use turbulence::{Turbulence, TurbulencePlugin};
+use wind::{Wind, WindPlugin};
 
 fn main() {
     App::new()
         .insert_resource(Msaa::default())
         .insert_resource(ClearColor(Color::rgb(0.52, 0.81, 0.92)))
-        .add_plugins(DefaultPlugins)
+        .insert_resource(wind())
+        .add_plugins(DefaultPlugins.set(AssetPlugin {
+            // Aircraft definitions are reloaded when they change on disk.
+            watch_for_changes: true,
+            ..default()
+        }))
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         // .add_plugin(RapierDebugRenderPlugin::default())
         .add_plugin(DebugLinesPlugin::default())
         .add_plugin(WorldInspectorPlugin)
         .add_plugin(AirplanePlugin)
+        .add_plugin(ColliderPlugin)
+        .add_plugin(AircraftPlugin)
+        .add_plugin(ModelPlugin)
         .add_plugin(AirplaneHudPlugin)
+        .add_plugin(AtmospherePlugin)
+        .add_plugin(WindPlugin)
+        .add_plugin(TurbulencePlugin)
+        .add_plugin(SoaringPlugin)
         .add_plugin(AerodynamicsPlugin)
+        .add_plugin(ControlsPlugin)
+        .add_plugin(PowerplantPlugin)
+        .add_plugin(FuelPlugin)
+        .add_plugin(BallastPlugin)
+        .add_plugin(MassPlugin)
+        .add_plugin(LandingGearPlugin)
+        .add_plugin(CrashPlugin)
+        .add_plugin(StructurePlugin)
         .add_plugin(CameraPlugin)
         .add_plugin(InputPlugin)
         .add_startup_system(setup_terrain)
@@ -42,6 +90,21 @@ fn main() {
         .run();
 }
 
+// Calm air without turbulence, unless wind fields are loaded with `--wind <file>`, e.g.
+// `cargo run -- --wind assets/wind/demo.ron`.
+fn wind() -> Wind {
+    let args: Vec<String> = std::env::args().collect();
+    let path = match args.iter().position(|arg| arg == "--wind") {
+        Some(index) => args.get(index + 1),
+        None => return Wind::default(),
+    };
+    match path.map(|path| Wind::load(path)) {
+        Some(Ok(wind)) => wind,
+        Some(Err(error)) => panic!("Failed to load the wind fields: {}", error),
+        None => panic!("--wind needs a file"),
+    }
+}
+
 #[derive(Component)]
 struct Sun;
 
@@ -50,6 +113,7 @@ fn setup_terrain(
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut soaring_weather: ResMut<SoaringWeather>,
 ) {
     let ground_size = 25_000.0;
 
@@ -71,6 +135,33 @@ fn setup_terrain(
         ColliderDebugColor(Color::GREEN),
     ));
 
+    // A ridge ahead of the airplane's start, across its track. Its terrain is both rendered
+    // and used as a collider, and it produces ridge lift when the wind blows against it.
+    let ridge = Ridge {
+        start: Vec2::new(-1500.0, -3000.0),
+        end: Vec2::new(1500.0, -3000.0),
+        height: 150.0,
+        half_width: 300.0,
+    };
+    let ridge_mesh = ridge.terrain_mesh(25.0);
+    let ridge_collider = Collider::from_bevy_mesh(&ridge_mesh, &ComputedColliderShape::TriMesh)
+        .expect("the ridge mesh is a triangle list");
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(ridge_mesh),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.36, 0.45, 0.25),
+                perceptual_roughness: 1.0,
+                ..default()
+            }),
+            ..default()
+        },
+        ridge_collider,
+        Restitution::coefficient(0.1),
+        ColliderDebugColor(Color::GREEN),
+    ));
+    soaring_weather.ridges.push(ridge);
+
     commands.spawn((
         DirectionalLightBundle {
             directional_light: DirectionalLight {
@@ -94,155 +185,43 @@ fn setup_terrain(
     ));
 }
 
-fn setup_airplane(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let fuselage_collider = load_collider("assets/planes/ask21/ASK21_Fuselage_Collider.csv");
-    let left_wing_collider = load_collider("assets/planes/ask21/ASK21_Left_Wing_Collider.csv");
-    let right_wing_collider = load_collider("assets/planes/ask21/ASK21_Right_Wing_Collider.csv");
-    let horizontal_stabilizer_collider =
-        load_collider("assets/planes/ask21/ASK21_Horizontal_Stabilizer_Collider.csv");
-    let vertical_stabilizer_collider =
-        load_collider("assets/planes/ask21/ASK21_Vertical_Stabilizer_Collider.csv");
+// The ASK 21, unless another definition is given with `--aircraft <file>` relative to the
+// assets folder, e.g. `cargo run -- --aircraft planes/ask21/ask21mi.aircraft`.
+fn aircraft_path() -> String {
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|arg| arg == "--aircraft") {
+        Some(index) => args
+            .get(index + 1)
+            .expect("--aircraft needs a file")
+            .clone(),
+        None => "planes/ask21/ask21.aircraft".to_string(),
+    }
+}
 
-    commands
-        .spawn((
-            AirplaneBundle {
-                transform: Transform::from_xyz(0.0, 1000.0, 0.0)
-                    .with_rotation(Quat::from_rotation_x(0.0f32.to_radians())),
-                velocity: Velocity::linear(Vec3::new(0.0, 0.0, -27.7)),
-                collider: Collider::compound(vec![
-                    (
-                        Vec3::ZERO,
-                        Quat::IDENTITY,
-                        Collider::convex_hull(&fuselage_collider).unwrap(),
-                    ),
-                    (
-                        Vec3::ZERO,
-                        Quat::IDENTITY,
-                        Collider::convex_hull(&left_wing_collider).unwrap(),
-                    ),
-                    (
-                        Vec3::ZERO,
-                        Quat::IDENTITY,
-                        Collider::convex_hull(&right_wing_collider).unwrap(),
-                    ),
-                    (
-                        Vec3::ZERO,
-                        Quat::IDENTITY,
-                        Collider::convex_hull(&horizontal_stabilizer_collider).unwrap(),
-                    ),
-                    (
-                        Vec3::ZERO,
-                        Quat::IDENTITY,
-                        Collider::convex_hull(&vertical_stabilizer_collider).unwrap(),
-                    ),
-                ]),
-                aero_surface_list: AeroSurfaceList {
-                    surfaces: vec![
-                        (
-                            // left wing
-                            AeroSurface {
-                                config: AeroSurfaceConfig {
-                                    span: 8.0,
-                                    chord: 1.2,
-                                    zero_lift_aoa: 3.0f32.to_radians(),
-                                    control_surface_fraction: 0.2,
-                                    ..default()
-                                },
-                                input_type: ControlInputType::Roll,
-                                input_sensitivity: -0.3,
-                                ..default()
-                            },
-                            Transform::from_xyz(-4.5, 1.0, 0.2)
-                                .with_rotation(Quat::from_rotation_z(-0.07)),
-                        ),
-                        (
-                            // right wing
-                            AeroSurface {
-                                config: AeroSurfaceConfig {
-                                    span: 8.0,
-                                    chord: 1.2,
-                                    zero_lift_aoa: 3.0f32.to_radians(),
-                                    control_surface_fraction: 0.2,
-                                    ..default()
-                                },
-                                input_type: ControlInputType::Roll,
-                                input_sensitivity: 0.3,
-                                ..default()
-                            },
-                            Transform::from_xyz(4.5, 1.0, 0.2)
-                                .with_rotation(Quat::from_rotation_z(0.07)),
-                        ),
-                        (
-                            // fuselage
-                            AeroSurface {
-                                config: AeroSurfaceConfig {
-                                    span: 0.8,
-                                    chord: 8.5,
-                                    ..default()
-                                },
-                                input_type: ControlInputType::None,
-                                ..default()
-                            },
-                            Transform::from_xyz(0.0, 0.6, 1.2)
-                                .with_rotation(Quat::from_rotation_z(PI * 0.5)),
-                        ),
-                        (
-                            // vertical stabilizer
-                            AeroSurface {
-                                config: AeroSurfaceConfig {
-                                    span: 1.5,
-                                    chord: 1.0,
-                                    control_surface_fraction: 0.3,
-                                    ..default()
-                                },
-                                input_type: ControlInputType::Yaw,
-                                input_sensitivity: -0.5,
-                                ..default()
-                            },
-                            Transform::from_xyz(0.0, 1.3, 4.9)
-                                .with_rotation(Quat::from_rotation_z(PI * 0.5)),
-                        ),
-                        (
-                            // horizontal stabilizer
-                            AeroSurface {
-                                config: AeroSurfaceConfig {
-                                    span: 3.0,
-                                    chord: 0.8,
-                                    control_surface_fraction: 0.3,
-                                    ..default()
-                                },
-                                input_type: ControlInputType::Pitch,
-                                input_sensitivity: -0.5,
-                                ..default()
-                            },
-                            Transform::from_xyz(0.0, 2.0, 4.9)
-                                .with_rotation(Quat::from_rotation_x(-0.05)),
-                        ),
-                    ],
-                },
-                ..default()
-            },
-            Name::new("Airplane"),
-            ColliderMassProperties::MassProperties(MassProperties {
-                local_center_of_mass: Vec3::new(-0.08496038, 0.86599594, -0.0),
-                mass: 530.0,
-                principal_inertia_local_frame: Quat::from_xyzw(
-                    0.44778442,
-                    -0.008306614,
-                    0.0017128434,
-                    0.8941014,
-                ),
-                principal_inertia: Vec3::new(6293.8193, 5342.917, 5116.539),
-            }),
-        ))
-        .with_children(|child_builder| {
-            child_builder
-                .spawn(SceneBundle {
-                    scene: asset_server.load("planes/ask21/ask21.glb#Scene0"),
-                    ..default()
-                })
-                .insert(Transform::from_rotation(Quat::from_rotation_y(PI)));
-        });
+fn setup_airplane(mut commands: Commands, asset_server: Res<AssetServer>, wind: Res<Wind>) {
+    // The surfaces, colliders, mass and model are added once the definition is loaded.
+    let definition: Handle<AircraftDefinition> = asset_server.load(aircraft_path().as_str());
+
+    let transform = Transform::from_xyz(0.0, 1000.0, 0.0)
+        .with_rotation(Quat::from_rotation_x(0.0f32.to_radians()));
+    let velocity = Velocity::linear(Vec3::new(0.0, 0.0, -27.7));
+
+    let mut airplane = commands.spawn((
+        AirplaneBundle {
+            transform,
+            velocity,
+            ..default()
+        },
+        definition,
+        Name::new("Airplane"),
+        ColliderMassProperties::default(),
+        CrashDetection::default(),
+        Respawn::new(transform, velocity),
+    ));
+    // Both versions of the ASK 21 span 17 m.
+    if let Some(turbulence) = wind.turbulence {
+        airplane.insert(Turbulence::from_config(turbulence, 17.0));
+    }
 }
 
 fn shadowmap_follow_airplane(
@@ -251,18 +230,3 @@ fn shadowmap_follow_airplane(
 ) {
     sun_query.single_mut().translation = airplane_query.single().translation;
 }
-
-fn load_collider(path: &str) -> Vec<Vec3> {
-    std::fs::read_to_string(path)
-        .unwrap()
-        .lines()
-        .map(|line| {
-            let mut components = line.split(';').map(|s| s.parse::<f32>().unwrap());
-            Vec3::new(
-                -components.next().unwrap(),
-                components.next().unwrap(),
-                -components.next().unwrap(),
-            )
-        })
-        .collect::<Vec<_>>()
-}
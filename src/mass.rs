@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::dynamics::MassProperties as RapierMassProperties;
+use serde::Deserialize;
+
+use crate::ballast::{dump_water_ballast, WaterBallast};
+use crate::fuel::{consume_fuel, FuelSystem};
+
+pub struct MassPlugin;
+
+impl Plugin for MassPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MassBalance>().add_system(
+            update_mass_properties
+                .after(consume_fuel)
+                .after(dump_water_ballast),
+        );
+    }
+}
+
+// A named location in the airplane's local frame that carries a variable load, like a pilot
+// seat or a ballast box.
+#[derive(Reflect, FromReflect, Deserialize, Clone)]
+pub struct PayloadStation {
+    pub name: String,
+    pub position: Vec3,
+    pub mass: f32,     // kg
+    pub max_mass: f32, // kg
+}
+
+// Allowed range of the center of gravity along the airplane's longitudinal axis (local z,
+// pointing back).
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+pub struct CenterOfGravityLimits {
+    pub forward: f32, // meters
+    pub aft: f32,     // meters
+}
+
+// Mass properties of the empty airframe plus the payload. The rigid body's mass, center of
+// mass and inertia are recomputed from these, the fuel and the water ballast whenever they
+// change.
+#[derive(Reflect, Component, Clone)]
+pub struct MassBalance {
+    pub empty_mass: MassProperties,
+    pub stations: Vec<PayloadStation>,
+    pub center_of_gravity_limits: Option<CenterOfGravityLimits>,
+    // Set while the loading is invalid, so that each airplane reports it only once.
+    pub invalid_loading: bool,
+}
+
+impl MassBalance {
+    pub fn mass_properties(
+        &self,
+        fuel_system: Option<&FuelSystem>,
+        water_ballast: Option<&WaterBallast>,
+    ) -> MassProperties {
+        let mut mass_properties = self.empty_mass.into_rapier(1.0);
+        for station in &self.stations {
+            mass_properties += point_mass(station.position, station.mass);
+        }
+        if let Some(fuel_system) = fuel_system {
+            for tank in &fuel_system.tanks {
+                mass_properties += point_mass(tank.position, tank.quantity);
+            }
+        }
+        if let Some(water_ballast) = water_ballast {
+            for tank in &water_ballast.tanks {
+                mass_properties += point_mass(tank.position, tank.quantity);
+            }
+        }
+        MassProperties::from_rapier(mass_properties, 1.0)
+    }
+}
+
+// A point mass at a position in the airplane's local frame. It contributes to the inertia
+// only through its distance from the center of mass.
+pub fn point_mass(position: Vec3, mass: f32) -> RapierMassProperties {
+    MassProperties {
+        local_center_of_mass: position,
+        mass,
+        principal_inertia_local_frame: Quat::IDENTITY,
+        principal_inertia: Vec3::ZERO,
+    }
+    .into_rapier(1.0)
+}
+
+fn update_mass_properties(
+    mut airplane_query: Query<
+        (
+            &mut MassBalance,
+            Option<&FuelSystem>,
+            Option<&WaterBallast>,
+            &mut ColliderMassProperties,
+            Option<&Name>,
+        ),
+        Or<(
+            Changed<MassBalance>,
+            Changed<FuelSystem>,
+            Changed<WaterBallast>,
+        )>,
+    >,
+) {
+    for (mut mass_balance, fuel_system, water_ballast, mut collider_mass_properties, name) in
+        &mut airplane_query
+    {
+        let mass_properties = mass_balance.mass_properties(fuel_system, water_ballast);
+        *collider_mass_properties = ColliderMassProperties::MassProperties(mass_properties);
+
+        // Only warn once the loading changes from valid to invalid, not on every update
+        // while fuel is burned.
+        let name = name.map_or("airplane", |name| name.as_str());
+        let mut valid = true;
+        for station in &mass_balance.stations {
+            if station.mass > station.max_mass {
+                valid = false;
+                if !mass_balance.invalid_loading {
+                    warn!(
+                        "{}: {} carries {:.0} kg, more than the maximum of {:.0} kg",
+                        name, station.name, station.mass, station.max_mass
+                    );
+                }
+            }
+        }
+        if let Some(limits) = mass_balance.center_of_gravity_limits {
+            let center_of_gravity = mass_properties.local_center_of_mass.z;
+            if center_of_gravity < limits.forward || center_of_gravity > limits.aft {
+                valid = false;
+                if !mass_balance.invalid_loading {
+                    warn!(
+                        "{}: center of gravity at {:.3} m is outside the limits {:.3} m to {:.3} m",
+                        name, center_of_gravity, limits.forward, limits.aft
+                    );
+                }
+            }
+        }
+        // The flag is bookkeeping only and must not trigger another mass update.
+        mass_balance.bypass_change_detection().invalid_loading = !valid;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::AircraftDefinition;
+
+    fn mass_balance(source: &str) -> MassBalance {
+        let definition: AircraftDefinition = ron::from_str(source).unwrap();
+        definition.mass_balance()
+    }
+
+    fn ask21() -> MassBalance {
+        mass_balance(include_str!("../assets/planes/ask21/ask21.aircraft"))
+    }
+
+    #[test]
+    fn ask21_stations_give_mass_and_center_of_gravity() {
+        let mass_properties = ask21().mass_properties(None, None);
+        assert!((mass_properties.mass - 530.0).abs() < 1e-3);
+        let center_of_gravity = mass_properties.local_center_of_mass;
+        assert!(
+            center_of_gravity.abs_diff_eq(Vec3::new(-0.085, 0.866, 0.0), 1e-3),
+            "center of gravity at {}",
+            center_of_gravity
+        );
+        // The pilots ahead of the empty center of mass add to the pitch inertia.
+        assert!(mass_properties.principal_inertia.x > 5420.0);
+    }
+
+    #[test]
+    fn fuel_moves_the_center_of_gravity() {
+        let source = include_str!("../assets/planes/ask21/ask21mi.aircraft");
+        let definition: AircraftDefinition = ron::from_str(source).unwrap();
+        let mut fuel_system = FuelSystem {
+            tanks: definition.fuel_tanks.clone(),
+            feed: definition.fuel_feed,
+        };
+        let mass_balance = definition.mass_balance();
+
+        let full = mass_balance.mass_properties(Some(&fuel_system), None);
+        fuel_system.draw(10.0);
+        let empty = mass_balance.mass_properties(Some(&fuel_system), None);
+        assert!((full.mass - empty.mass - 10.0).abs() < 1e-3);
+        // The tank is behind the center of gravity.
+        assert!(full.local_center_of_mass.z > empty.local_center_of_mass.z);
+    }
+
+    fn run_update_mass_properties(world: &mut World) {
+        SystemStage::single(update_mass_properties).run(world);
+    }
+
+    #[test]
+    fn loading_outside_the_limits_is_flagged() {
+        let mut world = World::new();
+        let airplane = world
+            .spawn((ask21(), ColliderMassProperties::default()))
+            .id();
+
+        run_update_mass_properties(&mut world);
+        let entity = world.entity(airplane);
+        assert!(!entity.get::<MassBalance>().unwrap().invalid_loading);
+        match entity.get::<ColliderMassProperties>().unwrap() {
+            ColliderMassProperties::MassProperties(mass_properties) => {
+                assert!((mass_properties.mass - 530.0).abs() < 1e-3)
+            }
+            _ => panic!("mass properties not set"),
+        }
+
+        // Without pilots the center of gravity is 0.85 m back, behind the aft limit.
+        let set_station_masses = |world: &mut World, masses: [f32; 3]| {
+            let mut mass_balance = world.get_mut::<MassBalance>(airplane).unwrap();
+            for (station, mass) in mass_balance.stations.iter_mut().zip(masses) {
+                station.mass = mass;
+            }
+        };
+        set_station_masses(&mut world, [0.0, 0.0, 0.0]);
+        run_update_mass_properties(&mut world);
+        assert!(world.get::<MassBalance>(airplane).unwrap().invalid_loading);
+
+        set_station_masses(&mut world, [85.0, 85.0, 0.0]);
+        run_update_mass_properties(&mut world);
+        assert!(!world.get::<MassBalance>(airplane).unwrap().invalid_loading);
+
+        // Two heavy pilots and full nose ballast move it ahead of the forward limit.
+        set_station_masses(&mut world, [110.0, 110.0, 15.0]);
+        run_update_mass_properties(&mut world);
+        assert!(world.get::<MassBalance>(airplane).unwrap().invalid_loading);
+
+        // An overloaded seat is invalid even with the center of gravity in range.
+        set_station_masses(&mut world, [120.0, 85.0, 0.0]);
+        run_update_mass_properties(&mut world);
+        assert!(world.get::<MassBalance>(airplane).unwrap().invalid_loading);
+    }
+}
@@ -0,0 +1,335 @@
+use bevy::gltf::GltfExtras;
+use bevy::prelude::*;
+use bevy::scene::SceneInstance;
+use serde::Deserialize;
+
+use crate::aerodynamics::{
+    update_control_surface_angle, AeroSurface, AeroSurfaceConfig, AeroSurfaceList, ControlInputType,
+};
+use crate::controls::{update_canopy, Canopy};
+
+pub struct ModelPlugin;
+
+impl Plugin for ModelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(build_gltf_surfaces)
+            .add_system(find_animated_parts)
+            .add_system(
+                animate_model
+                    .after(find_animated_parts)
+                    .after(update_control_surface_angle)
+                    .after(update_canopy),
+            );
+    }
+}
+
+// The scenes spawned as children of an airplane, once all of them are ready.
+pub fn spawned_scenes(
+    children: &Children,
+    scene_query: &Query<&SceneInstance>,
+    scene_spawner: &SceneSpawner,
+) -> Option<Vec<Entity>> {
+    let mut scenes = Vec::new();
+    for &child in children.iter() {
+        if let Ok(instance) = scene_query.get(child) {
+            if !scene_spawner.instance_is_ready(**instance) {
+                return None;
+            }
+            scenes.push(child);
+        }
+    }
+    (!scenes.is_empty()).then_some(scenes)
+}
+
+// Calls `visit` for a node and all its descendants, with their transforms relative to the
+// airplane. Global transforms of freshly spawned scenes are not propagated yet, so the local
+// transforms are accumulated while walking down the hierarchy.
+pub fn visit_hierarchy(
+    node: Entity,
+    transform: Transform,
+    hierarchy_query: &Query<(&Transform, Option<&Children>)>,
+    visit: &mut impl FnMut(Entity, Transform),
+) {
+    visit(node, transform);
+    if let Ok((_, Some(children))) = hierarchy_query.get(node) {
+        for &child in children.iter() {
+            if let Ok((child_transform, _)) = hierarchy_query.get(child) {
+                visit_hierarchy(
+                    child,
+                    transform.mul_transform(*child_transform),
+                    hierarchy_query,
+                    visit,
+                );
+            }
+        }
+    }
+}
+
+// Visits every node of the scenes spawned below an airplane.
+pub fn visit_scenes(
+    scenes: &[Entity],
+    hierarchy_query: &Query<(&Transform, Option<&Children>)>,
+    visit: &mut impl FnMut(Entity, Transform),
+) {
+    for &scene in scenes {
+        if let Ok((transform, _)) = hierarchy_query.get(scene) {
+            visit_hierarchy(scene, *transform, hierarchy_query, visit);
+        }
+    }
+}
+
+// Custom properties of an empty in the model that describes an aerodynamic surface, as
+// exported to the glTF extras, e.g.
+//   {"span": 8.0, "chord": 1.2, "control_surface_fraction": 0.2, "input_type": "Roll",
+//    "sensitivity": -0.3}
+// Any other `AeroSurfaceConfig` field can be given as well. The surface is placed at the
+// empty, with its local x axis along the span, y up and z towards the trailing edge.
+#[derive(Deserialize)]
+struct SurfaceExtras {
+    #[serde(flatten)]
+    config: AeroSurfaceConfig,
+    #[serde(default)]
+    input_type: ControlInputType,
+    #[serde(default)]
+    sensitivity: f32,
+}
+
+impl SurfaceExtras {
+    fn parse(extras: &GltfExtras) -> Option<Self> {
+        let value = serde_json::from_str::<serde_json::Value>(&extras.value).ok()?;
+        if value.get("span").is_none() || value.get("chord").is_none() {
+            return None;
+        }
+        match serde_json::from_value(value) {
+            Ok(surface_extras) => Some(surface_extras),
+            Err(error) => {
+                warn!(
+                    "Invalid aero surface properties {}: {}",
+                    extras.value, error
+                );
+                None
+            }
+        }
+    }
+}
+
+// Adds the surfaces described by empties in the model to the airplane's surface list, after
+// the surfaces of the aircraft definition. The component is removed once the surfaces have
+// been added.
+#[derive(Component, Clone, Copy)]
+pub struct GltfSurfaces;
+
+fn build_gltf_surfaces(
+    mut commands: Commands,
+    mut airplane_query: Query<(Entity, &mut AeroSurfaceList, &Children), With<GltfSurfaces>>,
+    hierarchy_query: Query<(&Transform, Option<&Children>)>,
+    extras_query: Query<(Option<&Name>, &GltfExtras)>,
+    scene_query: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+) {
+    for (entity, mut surface_list, children) in &mut airplane_query {
+        let scenes = match spawned_scenes(children, &scene_query, &scene_spawner) {
+            Some(scenes) => scenes,
+            None => continue,
+        };
+
+        let mut surfaces = Vec::new();
+        visit_scenes(&scenes, &hierarchy_query, &mut |node, transform| {
+            let (name, extras) = match extras_query.get(node) {
+                Ok(components) => components,
+                Err(_) => return,
+            };
+            if let Some(surface_extras) = SurfaceExtras::parse(extras) {
+                let name = name.map_or("without name", |name| name.as_str());
+                if let Err(reason) = surface_extras.config.validate() {
+                    error!("Ignoring aero surface {} from the model: {}", name, reason);
+                    return;
+                }
+                info!("Adding aero surface {} from the model", name);
+                surfaces.push((
+                    AeroSurface {
+                        name: name.to_string(),
+                        config: surface_extras.config,
+                        input_type: surface_extras.input_type,
+                        input_sensitivity: surface_extras.sensitivity,
+                        ..default()
+                    },
+                    // The size of the surface is given by span and chord, not by the scale
+                    // of the empty.
+                    transform.with_scale(Vec3::ONE),
+                ));
+            }
+        });
+
+        surface_list.surfaces.extend(surfaces);
+
+        // Wakes may refer to the surfaces from the model, which are only known now.
+        let surface_count = surface_list.surfaces.len();
+        surface_list
+            .wakes
+            .retain(|wake| match wake.validate(surface_count) {
+                Ok(()) => true,
+                Err(reason) => {
+                    error!(
+                        "Ignoring wake of surface {} behind surface {}: {}",
+                        wake.downstream_surface, wake.upstream_surface, reason
+                    );
+                    false
+                }
+            });
+        commands.entity(entity).remove::<GltfSurfaces>();
+    }
+}
+
+// State of the airplane that moves a part of the model.
+#[derive(Deserialize, Clone)]
+pub enum PartInput {
+    // `control_surface_angle` of the surface with the given name, in radians
+    ControlSurface(String),
+    // `airbrake_deployment` of the surface with the given name, from 0 to 1
+    Airbrake(String),
+    // Opening of the canopy, from 0 (closed) to 1 (open)
+    Canopy,
+}
+
+impl PartInput {
+    pub fn surface(&self) -> Option<&str> {
+        match self {
+            PartInput::ControlSurface(surface) | PartInput::Airbrake(surface) => Some(surface),
+            PartInput::Canopy => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum PartMotion {
+    // Rotation about a non-zero axis through the node's origin, which should lie on the hinge
+    // line, in the node's local frame. Positive inputs rotate counterclockwise when looking against
+    // the axis.
+    Hinge { axis: Vec3 },
+    // Translation along a non-zero axis in the node's local frame, by `distance` times the
+    // input.
+    Slide { axis: Vec3, distance: f32 },
+}
+
+// A named node of the model that follows a control surface, e.g. an aileron hinged at its
+// leading edge or an airbrake paddle extending from the wing.
+#[derive(Deserialize, Clone)]
+pub struct AnimatedPart {
+    pub node: String,
+    pub input: PartInput,
+    pub motion: PartMotion,
+}
+
+// Moves nodes of the airplane's model according to its control surfaces. The nodes are
+// looked up by name once the model has been spawned; their transforms at that time are the
+// rest positions.
+#[derive(Component, Clone)]
+pub struct ModelAnimation {
+    pub parts: Vec<AnimatedPart>,
+    nodes: Option<Vec<Option<(Entity, Transform)>>>,
+}
+
+impl ModelAnimation {
+    pub fn new(parts: Vec<AnimatedPart>) -> Self {
+        Self { parts, nodes: None }
+    }
+
+    // Stops animating the parts of a surface removed from the airplane's `AeroSurfaceList`.
+    pub fn detach_surface(&mut self, name: &str) {
+        let mut part_index = 0;
+        while part_index < self.parts.len() {
+            if self.parts[part_index].input.surface() == Some(name) {
+                self.parts.remove(part_index);
+                if let Some(nodes) = &mut self.nodes {
+                    nodes.remove(part_index);
+                }
+            } else {
+                part_index += 1;
+            }
+        }
+    }
+}
+
+fn find_animated_parts(
+    mut airplane_query: Query<(&mut ModelAnimation, &Children)>,
+    hierarchy_query: Query<(&Transform, Option<&Children>)>,
+    name_query: Query<&Name>,
+    scene_query: Query<&SceneInstance>,
+    scene_spawner: Res<SceneSpawner>,
+) {
+    for (mut model_animation, children) in &mut airplane_query {
+        if model_animation.nodes.is_some() {
+            continue;
+        }
+        let scenes = match spawned_scenes(children, &scene_query, &scene_spawner) {
+            Some(scenes) => scenes,
+            None => continue,
+        };
+
+        let mut nodes = vec![None; model_animation.parts.len()];
+        visit_scenes(&scenes, &hierarchy_query, &mut |node, _| {
+            let name = match name_query.get(node) {
+                Ok(name) => name,
+                Err(_) => return,
+            };
+            for (index, part) in model_animation.parts.iter().enumerate() {
+                if nodes[index].is_none() && part.node == name.as_str() {
+                    if let Ok((transform, _)) = hierarchy_query.get(node) {
+                        nodes[index] = Some((node, *transform));
+                    }
+                }
+            }
+        });
+
+        for (part, node) in model_animation.parts.iter().zip(&nodes) {
+            if node.is_none() {
+                warn!(
+                    "Animated part: the model has no node named \"{}\"",
+                    part.node
+                );
+            }
+        }
+        model_animation.nodes = Some(nodes);
+    }
+}
+
+fn animate_model(
+    airplane_query: Query<(&ModelAnimation, &AeroSurfaceList, Option<&Canopy>)>,
+    mut node_query: Query<&mut Transform>,
+) {
+    for (model_animation, surface_list, canopy) in &airplane_query {
+        let nodes = match &model_animation.nodes {
+            Some(nodes) => nodes,
+            None => continue,
+        };
+
+        for (part, node) in model_animation.parts.iter().zip(nodes) {
+            let (entity, rest_transform) = match node {
+                Some(node) => *node,
+                None => continue,
+            };
+            let input = match &part.input {
+                PartInput::ControlSurface(name) => surface_list
+                    .surface(name)
+                    .map_or(0.0, |surface| surface.control_surface_angle),
+                PartInput::Airbrake(name) => surface_list
+                    .surface(name)
+                    .map_or(0.0, |surface| surface.airbrake_deployment),
+                PartInput::Canopy => canopy.map_or(0.0, |canopy| canopy.opening),
+            };
+
+            if let Ok(mut transform) = node_query.get_mut(entity) {
+                *transform = match part.motion {
+                    PartMotion::Hinge { axis } => rest_transform.with_rotation(
+                        rest_transform.rotation * Quat::from_axis_angle(axis.normalize(), input),
+                    ),
+                    PartMotion::Slide { axis, distance } => rest_transform.with_translation(
+                        rest_transform.translation
+                            + rest_transform.rotation * axis.normalize() * distance * input,
+                    ),
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,750 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+use crate::aerodynamics::{simulate_aerodynamics, AmbientAir};
+use crate::atmosphere::{Atmosphere, ISA_SEA_LEVEL_DENSITY};
+use crate::controls::{update_throttle_lever, ThrottleLever};
+use crate::input::{AirplaneAction, AirplaneControls};
+use crate::soaring::SoaringWeather;
+use crate::wind::Wind;
+
+pub struct PowerplantPlugin;
+
+impl Plugin for PowerplantPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Powerplant>()
+            .register_type::<JetEngine>()
+            .add_system(
+                update_engine_controls
+                    .after(update_throttle_lever)
+                    .before(simulate_powerplants)
+                    .before(simulate_jet_engines),
+            )
+            .add_system(simulate_powerplants.after(simulate_aerodynamics))
+            .add_system(simulate_jet_engines.after(simulate_aerodynamics));
+    }
+}
+
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct PistonEngine {
+    pub max_power: f32, // W, at sea level and rated rpm
+    pub rated_rpm: f32, // rpm
+    pub idle_rpm: f32,  // rpm, the engine stalls below this speed
+    pub idle_power_fraction: f32,
+    pub friction_power_fraction: f32,
+    pub specific_fuel_consumption: f32, // kg/J
+    pub starter_torque: f32,            // N·m, at the propeller shaft
+}
+
+impl Default for PistonEngine {
+    fn default() -> Self {
+        Self {
+            max_power: 120_000.0, // W (= 160 hp)
+            rated_rpm: 2700.0,
+            idle_rpm: 500.0,
+            idle_power_fraction: 0.12,
+            friction_power_fraction: 0.05,
+            specific_fuel_consumption: 6.8e-8, // kg/J (= 0.4 lb/hp/h)
+            starter_torque: 80.0,
+        }
+    }
+}
+
+impl PistonEngine {
+    // Shaft power available at the given rpm. The power falls off with density altitude
+    // according to the Gagg-Ferrar relation.
+    pub fn power(&self, rpm: f32, throttle: f32, density_ratio: f32) -> f32 {
+        let x = (rpm / self.rated_rpm).clamp(0.0, 1.2);
+        let power_curve = x + x * x - x * x * x;
+        let altitude_factor = (density_ratio - (1.0 - density_ratio) / 7.55).max(0.0);
+        let throttle_factor =
+            self.idle_power_fraction + (1.0 - self.idle_power_fraction) * throttle;
+        self.max_power * power_curve * throttle_factor * altitude_factor
+    }
+
+    pub fn friction_power(&self, rpm: f32) -> f32 {
+        self.max_power * self.friction_power_fraction * (rpm / self.rated_rpm)
+    }
+}
+
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
+pub enum PropellerPitch {
+    #[default]
+    Fixed,
+    // A governor adjusts the blade pitch to keep the propeller at the given rpm.
+    ConstantSpeed {
+        governed_rpm: f32,
+        min_pitch: f32,
+        max_pitch: f32,
+    },
+}
+
+// Propeller with thrust and power coefficients depending on the advance ratio
+// J = V / (n * D):
+//   C_T = C_T0 * (1 - (J / J_0)²)
+//   C_P = C_P0 * (1 - 0.3 * (J / J_0)³)
+// where J_0 is the advance ratio of zero thrust, scaled by the blade pitch.
+#[derive(Reflect, FromReflect, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Propeller {
+    pub diameter: f32, // m
+    pub inertia: f32,  // kg·m², including the engine's rotating parts
+    pub static_thrust_coefficient: f32,
+    pub static_power_coefficient: f32,
+    pub zero_thrust_advance_ratio: f32,
+    pub pitch: PropellerPitch,
+    // Clockwise when seen from behind the propeller.
+    pub clockwise: bool,
+    // Lateral shift of the thrust line, as a fraction of the propeller radius, per unit of
+    // inflow angle (P-factor).
+    pub p_factor: f32,
+}
+
+impl Default for Propeller {
+    fn default() -> Self {
+        Self {
+            diameter: 1.9,
+            inertia: 3.0,
+            static_thrust_coefficient: 0.1,
+            static_power_coefficient: 0.045,
+            zero_thrust_advance_ratio: 1.0,
+            pitch: PropellerPitch::Fixed,
+            clockwise: true,
+            p_factor: 0.3,
+        }
+    }
+}
+
+impl Propeller {
+    pub fn thrust_coefficient(&self, advance_ratio: f32, pitch: f32) -> f32 {
+        let j = advance_ratio / (self.zero_thrust_advance_ratio * pitch);
+        self.static_thrust_coefficient * (1.0 - j * j)
+    }
+
+    pub fn power_coefficient(&self, advance_ratio: f32, pitch: f32) -> f32 {
+        let j = advance_ratio / (self.zero_thrust_advance_ratio * pitch);
+        // Scaling with the pitch accounts for the higher blade angle absorbing more power.
+        (self.static_power_coefficient * pitch * pitch * (1.0 - 0.3 * j * j * j)).max(0.0)
+    }
+}
+
+// Piston engine driving a propeller. The mount transform is relative to the airplane; the
+// propeller thrusts along the mount's forward direction.
+#[derive(Reflect, Component, Clone)]
+pub struct Powerplant {
+    pub engine: PistonEngine,
+    pub propeller: Propeller,
+    pub mount: Transform,
+    pub running: bool,
+    // While the starter is engaged it cranks a stopped engine, which fires once it turns at
+    // idle rpm.
+    pub starter: bool,
+    pub throttle: f32,
+    pub rpm: f32,
+    pub blade_pitch: f32,  // relative to the fixed pitch
+    pub thrust: f32,       // N
+    pub shaft_torque: f32, // N·m
+    pub fuel_flow: f32,    // kg/s
+}
+
+impl Default for Powerplant {
+    fn default() -> Self {
+        Self {
+            engine: PistonEngine::default(),
+            propeller: Propeller::default(),
+            mount: Transform::IDENTITY,
+            running: true,
+            starter: false,
+            throttle: 0.0,
+            rpm: 700.0,
+            blade_pitch: 1.0,
+            thrust: 0.0,
+            shaft_torque: 0.0,
+            fuel_flow: 0.0,
+        }
+    }
+}
+
+impl Powerplant {
+    // A running engine, turning a little above its idle rpm.
+    pub fn new(engine: PistonEngine, propeller: Propeller, mount: Transform) -> Self {
+        Self {
+            engine,
+            propeller,
+            mount,
+            rpm: 1.4 * engine.idle_rpm,
+            ..default()
+        }
+    }
+
+    // Restarts a running engine at idle, keeping the engine, propeller and mount.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.engine, self.propeller, self.mount);
+    }
+
+    // Advances the engine and propeller by one time step and returns the force and torque on
+    // the airplane, in the mount's local frame.
+    pub fn step(
+        &mut self,
+        local_air_velocity: Vec3,
+        air_density: f32,
+        delta_seconds: f32,
+    ) -> (Vec3, Vec3) {
+        let density_ratio = air_density / ISA_SEA_LEVEL_DENSITY;
+        let omega = (self.rpm * 2.0 * PI / 60.0).max(1.0);
+        let revolutions_per_second = omega / (2.0 * PI);
+
+        let shaft_power = if self.running {
+            self.engine.power(self.rpm, self.throttle, density_ratio)
+        } else {
+            0.0
+        };
+        let starter_torque = if self.starter && !self.running {
+            self.engine.starter_torque
+        } else {
+            0.0
+        };
+        let engine_torque =
+            (shaft_power - self.engine.friction_power(self.rpm)) / omega + starter_torque;
+
+        // Inflow along the propeller axis; air moving backwards relative to the airplane.
+        let axial_velocity = local_air_velocity.z.max(0.0);
+        let advance_ratio = axial_velocity / (revolutions_per_second * self.propeller.diameter);
+
+        if let PropellerPitch::ConstantSpeed {
+            governed_rpm,
+            min_pitch,
+            max_pitch,
+        } = self.propeller.pitch
+        {
+            // The governor coarsens the pitch when overspeeding and fines it when underspeeding.
+            let gain = 2.0;
+            self.blade_pitch = (self.blade_pitch
+                + gain * (self.rpm - governed_rpm) / governed_rpm * delta_seconds)
+                .clamp(min_pitch, max_pitch);
+        }
+
+        let n2 = revolutions_per_second * revolutions_per_second;
+        let diameter = self.propeller.diameter;
+        let thrust = self
+            .propeller
+            .thrust_coefficient(advance_ratio, self.blade_pitch)
+            * air_density
+            * n2
+            * diameter.powi(4);
+        let propeller_torque = self
+            .propeller
+            .power_coefficient(advance_ratio, self.blade_pitch)
+            * air_density
+            * n2
+            * diameter.powi(5)
+            / (2.0 * PI);
+
+        let angular_acceleration = (engine_torque - propeller_torque) / self.propeller.inertia;
+        let omega = (omega + angular_acceleration * delta_seconds).max(0.0);
+        self.rpm = omega * 60.0 / (2.0 * PI);
+        if self.rpm < self.engine.idle_rpm {
+            self.running = false;
+        } else if self.starter {
+            self.running = true;
+        }
+
+        self.thrust = thrust;
+        self.shaft_torque = engine_torque;
+        self.fuel_flow = shaft_power * self.engine.specific_fuel_consumption;
+
+        let rotation_sign = if self.propeller.clockwise { 1.0 } else { -1.0 };
+        let forward = Vec3::NEG_Z;
+        let force = forward * thrust;
+
+        // P-factor: with an inflow angle, the descending blade sees a higher angle of attack
+        // and the thrust line shifts towards it.
+        let disk_air_velocity = Vec3::new(local_air_velocity.x, local_air_velocity.y, 0.0);
+        let inflow_angle = disk_air_velocity
+            .length()
+            .atan2(local_air_velocity.z.abs().max(0.1));
+        let thrust_offset = rotation_sign
+            * forward.cross(disk_air_velocity.normalize_or_zero())
+            * self.propeller.p_factor
+            * inflow_angle
+            * diameter
+            * 0.5;
+
+        // The engine turns the propeller and reacts on the airframe in the opposite sense.
+        let torque_reaction = -rotation_sign * forward * engine_torque;
+
+        (force, thrust_offset.cross(force) + torque_reaction)
+    }
+}
+
+#[derive(Reflect, FromReflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JetRegime {
+    #[default]
+    Off,
+    Idle,
+    Dry,
+    Afterburner,
+}
+
+// Jet or turbofan engine. The fan speed N1 follows the throttle with first-order spool
+// dynamics; thrust scales with N1 and falls off with density and Mach number. The throttle
+// range above `afterburner_detent` lights the afterburner once the engine is at full dry
+// power. The mount transform is relative to the airplane; the engine thrusts along the
+// mount's forward direction.
+#[derive(Reflect, Component, Deserialize, Clone)]
+#[serde(default)]
+pub struct JetEngine {
+    pub max_thrust: f32,         // N, sea level static, dry
+    pub afterburner_thrust: f32, // N, sea level static, in addition to max_thrust
+    pub idle_n1: f32,            // fraction of maximum fan speed
+    pub idle_thrust_fraction: f32,
+    pub spool_up_time: f32,      // seconds
+    pub spool_down_time: f32,    // seconds
+    pub afterburner_time: f32,   // seconds
+    pub afterburner_detent: f32, // throttle position
+    pub specific_fuel_consumption: f32,
+    pub afterburner_specific_fuel_consumption: f32,
+    #[serde(skip)]
+    pub mount: Transform,
+    #[serde(skip)]
+    pub running: bool,
+    // While the starter is engaged it spins a stopped engine up to `starter_n1`, where the
+    // engine lights and accelerates to idle.
+    #[serde(skip)]
+    pub starter: bool,
+    pub starter_n1: f32,
+    #[serde(skip)]
+    pub throttle: f32,
+    #[serde(skip)]
+    pub n1: f32,
+    #[serde(skip)]
+    pub afterburner: f32, // 0 to 1
+    #[serde(skip)]
+    pub regime: JetRegime,
+    #[serde(skip)]
+    pub thrust: f32, // N
+    #[serde(skip)]
+    pub fuel_flow: f32, // kg/s
+}
+
+impl Default for JetEngine {
+    fn default() -> Self {
+        Self {
+            max_thrust: 50_000.0,
+            afterburner_thrust: 30_000.0,
+            idle_n1: 0.6,
+            idle_thrust_fraction: 0.05,
+            spool_up_time: 3.0,
+            spool_down_time: 1.5,
+            afterburner_time: 0.5,
+            afterburner_detent: 0.9,
+            specific_fuel_consumption: 2.2e-5, // kg/(N·s) (= 0.78 lb/lbf/h)
+            afterburner_specific_fuel_consumption: 5.7e-5, // kg/(N·s) (= 2.0 lb/lbf/h)
+            mount: Transform::IDENTITY,
+            running: true,
+            starter: false,
+            starter_n1: 0.25,
+            throttle: 0.0,
+            n1: 0.6,
+            afterburner: 0.0,
+            regime: JetRegime::Idle,
+            thrust: 0.0,
+            fuel_flow: 0.0,
+        }
+    }
+}
+
+impl JetEngine {
+    // Restarts a running engine at idle, keeping its configuration.
+    pub fn reset(&mut self) {
+        *self = Self {
+            running: true,
+            starter: false,
+            throttle: 0.0,
+            n1: self.idle_n1,
+            afterburner: 0.0,
+            regime: JetRegime::Idle,
+            thrust: 0.0,
+            fuel_flow: 0.0,
+            ..self.clone()
+        };
+    }
+
+    // Advances the spool by one time step and returns the thrust force in the mount's local
+    // frame.
+    pub fn step(&mut self, mach: f32, air_density: f32, delta_seconds: f32) -> Vec3 {
+        let has_afterburner = self.afterburner_thrust > 0.0;
+        let dry_throttle = if has_afterburner {
+            (self.throttle / self.afterburner_detent).min(1.0)
+        } else {
+            self.throttle
+        };
+
+        let target_n1 = if self.running {
+            self.idle_n1 + (1.0 - self.idle_n1) * dry_throttle
+        } else if self.starter {
+            self.starter_n1
+        } else {
+            0.0
+        };
+        let spool_time = if target_n1 > self.n1 {
+            self.spool_up_time
+        } else {
+            self.spool_down_time
+        };
+        self.n1 += (target_n1 - self.n1) * (1.0 - (-delta_seconds / spool_time).exp());
+        if !self.running && self.starter && self.n1 >= 0.95 * self.starter_n1 {
+            self.running = true;
+        }
+
+        let afterburner_selected = self.running
+            && has_afterburner
+            && self.throttle > self.afterburner_detent
+            && self.n1 > 0.95;
+        let target_afterburner = if afterburner_selected {
+            (self.throttle - self.afterburner_detent) / (1.0 - self.afterburner_detent)
+        } else {
+            0.0
+        };
+        self.afterburner += (target_afterburner - self.afterburner)
+            * (1.0 - (-delta_seconds / self.afterburner_time).exp());
+
+        self.regime = if !self.running {
+            JetRegime::Off
+        } else if self.afterburner > 0.01 {
+            JetRegime::Afterburner
+        } else if dry_throttle > 0.0 {
+            JetRegime::Dry
+        } else {
+            JetRegime::Idle
+        };
+
+        // Thrust increases roughly linearly with N1 between idle and full power. A windmilling
+        // engine produces no thrust.
+        let spool_fraction = ((self.n1 - self.idle_n1) / (1.0 - self.idle_n1)).max(0.0);
+        let dry_fraction = if self.n1 >= self.idle_n1 {
+            self.idle_thrust_fraction + (1.0 - self.idle_thrust_fraction) * spool_fraction
+        } else {
+            self.idle_thrust_fraction * self.n1 / self.idle_n1
+        };
+
+        // The dry thrust lapses with density and decreases with Mach number, while the
+        // afterburner profits from the ram pressure rise.
+        let density_ratio = air_density / ISA_SEA_LEVEL_DENSITY;
+        let dry_thrust =
+            self.max_thrust * dry_fraction * density_ratio.powf(0.7) * (1.0 - 0.3 * mach).max(0.0);
+        let afterburner_thrust = self.afterburner_thrust
+            * self.afterburner
+            * density_ratio.powf(0.7)
+            * (1.0 + 0.7 * mach);
+
+        // A stopped engine windmills down without producing thrust.
+        if self.running {
+            self.thrust = dry_thrust + afterburner_thrust;
+            self.fuel_flow = dry_thrust * self.specific_fuel_consumption
+                + afterburner_thrust * self.afterburner_specific_fuel_consumption;
+        } else {
+            self.thrust = 0.0;
+            self.fuel_flow = 0.0;
+        }
+
+        Vec3::NEG_Z * self.thrust
+    }
+}
+
+fn update_engine_controls(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut powerplant_query: Query<(&mut Powerplant, &ThrottleLever)>,
+    mut jet_engine_query: Query<(&mut JetEngine, &ThrottleLever)>,
+) {
+    let action_state = input_query.single();
+    let starter = action_state.pressed(AirplaneAction::Starter);
+
+    for (mut powerplant, throttle_lever) in &mut powerplant_query {
+        powerplant.throttle = throttle_lever.position;
+        powerplant.starter = starter;
+    }
+    for (mut jet_engine, throttle_lever) in &mut jet_engine_query {
+        jet_engine.throttle = throttle_lever.position;
+        jet_engine.starter = starter;
+    }
+}
+
+pub fn simulate_powerplants(
+    mut airplane_query: Query<(
+        &mut Powerplant,
+        &mut ExternalForce,
+        &ReadMassProperties,
+        &Transform,
+        &Velocity,
+    )>,
+    atmosphere: Res<Atmosphere>,
+    wind: Res<Wind>,
+    soaring_weather: Res<SoaringWeather>,
+    time: Res<Time>,
+) {
+    let ambient_air = AmbientAir {
+        atmosphere: &atmosphere,
+        wind: &wind,
+        soaring_weather: &soaring_weather,
+        time: time.elapsed_seconds(),
+    };
+
+    for (mut powerplant, mut external_force, read_mass_properties, transform, velocity) in
+        &mut airplane_query
+    {
+        let world_center_of_mass =
+            transform.transform_point(read_mass_properties.0.local_center_of_mass);
+        let mount_transform = transform.mul_transform(powerplant.mount);
+        let world_position = mount_transform.translation;
+        let relative_position = world_position - world_center_of_mass;
+
+        let air_velocity = ambient_air.wind_velocity(world_position)
+            - velocity.linvel
+            - velocity.angvel.cross(relative_position);
+        let local_air_velocity = mount_transform.rotation.conjugate() * air_velocity;
+
+        let (local_force, local_torque) = powerplant.step(
+            local_air_velocity,
+            ambient_air.density(world_position),
+            time.delta_seconds(),
+        );
+        let force = mount_transform.rotation * local_force;
+        let torque = mount_transform.rotation * local_torque;
+
+        external_force.force += force;
+        external_force.torque += relative_position.cross(force) + torque;
+    }
+}
+
+pub fn simulate_jet_engines(
+    mut airplane_query: Query<(
+        &mut JetEngine,
+        &mut ExternalForce,
+        &ReadMassProperties,
+        &Transform,
+        &Velocity,
+    )>,
+    atmosphere: Res<Atmosphere>,
+    wind: Res<Wind>,
+    soaring_weather: Res<SoaringWeather>,
+    time: Res<Time>,
+) {
+    let ambient_air = AmbientAir {
+        atmosphere: &atmosphere,
+        wind: &wind,
+        soaring_weather: &soaring_weather,
+        time: time.elapsed_seconds(),
+    };
+
+    for (mut jet_engine, mut external_force, read_mass_properties, transform, velocity) in
+        &mut airplane_query
+    {
+        let world_center_of_mass =
+            transform.transform_point(read_mass_properties.0.local_center_of_mass);
+        let mount_transform = transform.mul_transform(jet_engine.mount);
+        let world_position = mount_transform.translation;
+        let relative_position = world_position - world_center_of_mass;
+
+        let air_velocity = ambient_air.wind_velocity(world_position)
+            - velocity.linvel
+            - velocity.angvel.cross(relative_position);
+        let atmosphere_sample = ambient_air.atmosphere.sample(world_position.y);
+        let mach = air_velocity.length() / atmosphere_sample.speed_of_sound;
+
+        let local_force = jet_engine.step(mach, atmosphere_sample.density, time.delta_seconds());
+        let force = mount_transform.rotation * local_force;
+
+        external_force.force += force;
+        external_force.torque += relative_position.cross(force);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aircraft::AircraftDefinition;
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn propeller_coefficients_follow_the_advance_ratio() {
+        let propeller = Propeller::default();
+
+        assert_close(propeller.thrust_coefficient(0.0, 1.0), 0.1, 1e-6);
+        assert_close(propeller.thrust_coefficient(0.5, 1.0), 0.075, 1e-6);
+        assert_close(propeller.thrust_coefficient(1.0, 1.0), 0.0, 1e-6);
+        // A windmilling propeller beyond the zero thrust advance ratio produces drag.
+        assert!(propeller.thrust_coefficient(1.2, 1.0) < 0.0);
+        // A coarser pitch moves the zero thrust point to a higher advance ratio.
+        assert_close(propeller.thrust_coefficient(1.5, 1.5), 0.0, 1e-6);
+
+        assert_close(propeller.power_coefficient(0.0, 1.0), 0.045, 1e-6);
+        assert_close(propeller.power_coefficient(1.0, 1.0), 0.045 * 0.7, 1e-6);
+        assert_close(propeller.power_coefficient(0.0, 1.2), 0.045 * 1.44, 1e-6);
+        assert_eq!(propeller.power_coefficient(2.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn engine_power_lapses_with_density() {
+        let engine = PistonEngine::default();
+        let atmosphere = Atmosphere::default();
+
+        let sea_level_power = engine.power(engine.rated_rpm, 1.0, 1.0);
+        assert_close(sea_level_power, engine.max_power, 1.0);
+
+        // Gagg-Ferrar at 3000 m, with an ISA density ratio of 0.742
+        let density_ratio = atmosphere.density(3000.0) / ISA_SEA_LEVEL_DENSITY;
+        assert_close(density_ratio, 0.742, 0.002);
+        let power = engine.power(engine.rated_rpm, 1.0, density_ratio);
+        assert_close(power / sea_level_power, 0.708, 0.003);
+
+        // The thin air above about 17 km leaves no power at all.
+        assert_eq!(engine.power(engine.rated_rpm, 1.0, 0.1), 0.0);
+        // Closing the throttle leaves the idle power.
+        let idle_power = engine.power(engine.rated_rpm, 0.0, density_ratio);
+        assert_close(idle_power / power, engine.idle_power_fraction, 1e-4);
+    }
+
+    #[test]
+    fn ask21mi_climbs_at_full_throttle() {
+        let definition: AircraftDefinition =
+            ron::from_str(include_str!("../assets/planes/ask21/ask21mi.aircraft")).unwrap();
+        definition.validate().unwrap();
+        let mut powerplant = definition.powerplant().unwrap();
+        powerplant.throttle = 1.0;
+
+        // Let the rpm settle at 90 km/h.
+        let local_air_velocity = Vec3::new(0.0, 0.0, 25.0);
+        let mut force = Vec3::ZERO;
+        for _ in 0..1000 {
+            (force, _) = powerplant.step(local_air_velocity, ISA_SEA_LEVEL_DENSITY, 0.01);
+        }
+        assert!(powerplant.running);
+        assert!(
+            powerplant.rpm > 2000.0 && powerplant.rpm < 3200.0,
+            "{} rpm",
+            powerplant.rpm
+        );
+        // The thrust exceeds the drag of a glider with a 1:34 glide ratio by enough to climb
+        // at a few m/s.
+        let weight = 625.0 * 9.81;
+        let climb_rate = (-force.z - weight / 34.0) * 25.0 / weight;
+        assert!(
+            climb_rate > 1.0 && climb_rate < 5.0,
+            "climbs at {} m/s",
+            climb_rate
+        );
+    }
+
+    fn spool(jet_engine: &mut JetEngine, throttle: f32, seconds: f32) -> Vec3 {
+        jet_engine.throttle = throttle;
+        let mut force = Vec3::ZERO;
+        for _ in 0..(seconds / 0.01).round() as usize {
+            force = jet_engine.step(0.0, ISA_SEA_LEVEL_DENSITY, 0.01);
+        }
+        force
+    }
+
+    #[test]
+    fn n1_follows_the_throttle_with_the_spool_time_constants() {
+        let mut jet_engine = JetEngine::default();
+        assert_eq!(jet_engine.n1, jet_engine.idle_n1);
+
+        // After one time constant N1 has covered 63 % of the way to full dry power, which the
+        // throttle selects at the afterburner detent.
+        let detent = jet_engine.afterburner_detent;
+        let spool_up_time = jet_engine.spool_up_time;
+        spool(&mut jet_engine, detent, spool_up_time);
+        let expected = 0.6 + 0.4 * (1.0 - (-1.0f32).exp());
+        assert_close(jet_engine.n1, expected, 1e-3);
+        assert_eq!(jet_engine.regime, JetRegime::Dry);
+
+        spool(&mut jet_engine, detent, 30.0);
+        assert_close(jet_engine.n1, 1.0, 1e-3);
+        assert_close(jet_engine.thrust, jet_engine.max_thrust, 50.0);
+
+        // Spooling down is faster.
+        let spool_down_time = jet_engine.spool_down_time;
+        spool(&mut jet_engine, 0.0, spool_down_time);
+        let expected = 0.6 + 0.4 * (-1.0f32).exp();
+        assert_close(jet_engine.n1, expected, 1e-3);
+        assert_eq!(jet_engine.regime, JetRegime::Idle);
+    }
+
+    #[test]
+    fn afterburner_lights_at_full_dry_power() {
+        let mut jet_engine = JetEngine::default();
+
+        // The afterburner waits for the spool to reach full dry power.
+        spool(&mut jet_engine, 1.0, 0.5);
+        assert_eq!(jet_engine.afterburner, 0.0);
+        assert_eq!(jet_engine.regime, JetRegime::Dry);
+
+        let force = spool(&mut jet_engine, 1.0, 30.0);
+        assert_eq!(jet_engine.regime, JetRegime::Afterburner);
+        assert_close(jet_engine.afterburner, 1.0, 1e-3);
+        let thrust = jet_engine.max_thrust + jet_engine.afterburner_thrust;
+        assert_close(jet_engine.thrust, thrust, 50.0);
+        assert_close(-force.z, thrust, 50.0);
+        let fuel_flow = jet_engine.max_thrust * jet_engine.specific_fuel_consumption
+            + jet_engine.afterburner_thrust * jet_engine.afterburner_specific_fuel_consumption;
+        assert_close(jet_engine.fuel_flow, fuel_flow, 0.01);
+    }
+
+    #[test]
+    fn jet_thrust_lapses_with_density_and_mach() {
+        let mut jet_engine = JetEngine {
+            afterburner_thrust: 0.0,
+            ..default()
+        };
+        spool(&mut jet_engine, 1.0, 30.0);
+        let static_thrust = jet_engine.thrust;
+
+        jet_engine.step(0.5, ISA_SEA_LEVEL_DENSITY, 0.01);
+        assert_close(jet_engine.thrust / static_thrust, 0.85, 1e-3);
+
+        let density = Atmosphere::default().density(11_000.0);
+        jet_engine.step(0.0, density, 0.01);
+        let expected = (density / ISA_SEA_LEVEL_DENSITY).powf(0.7);
+        assert_close(jet_engine.thrust / static_thrust, expected, 1e-3);
+        assert!(expected < 0.45);
+    }
+
+    #[test]
+    fn jet_engine_from_the_definition_starts_at_idle() {
+        // The ASK 21 Mi with its piston engine replaced by a small jet
+        let source = include_str!("../assets/planes/ask21/ask21mi.aircraft");
+        let start = source.find("    engines: [").unwrap();
+        let end = start + source[start..].find("\n    ],\n").unwrap() + "\n    ],\n".len();
+        let source = format!(
+            "{}    engines: [Jet(engine: (max_thrust: 2000.0, afterburner_thrust: 0.0, \
+             idle_n1: 0.5), position: (0.0, 1.5, 2.0))],\n{}",
+            &source[..start],
+            &source[end..]
+        );
+        let definition: AircraftDefinition = ron::from_str(&source).unwrap();
+        definition.validate().unwrap();
+        assert!(definition.powerplant().is_none());
+
+        let jet_engine = definition.jet_engine().unwrap();
+        assert!(jet_engine.running);
+        assert_eq!(jet_engine.regime, JetRegime::Idle);
+        assert_eq!(jet_engine.max_thrust, 2000.0);
+        assert_eq!(jet_engine.n1, 0.5);
+        assert_eq!(jet_engine.spool_up_time, JetEngine::default().spool_up_time);
+        assert_eq!(jet_engine.mount.translation, Vec3::new(0.0, 1.5, 2.0));
+    }
+}
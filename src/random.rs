@@ -0,0 +1,38 @@
+use std::f32::consts::PI;
+
+// Small deterministic pseudo random number generator (SplitMix64), so that simulations
+// can be reproduced from a seed.
+#[derive(Default, Clone, Copy)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniformly distributed in the open interval (0, 1).
+    pub fn next_uniform(&mut self) -> f32 {
+        ((self.next_u64() >> 40) as f32 + 0.5) / (1u64 << 24) as f32
+    }
+
+    pub fn next_range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_uniform()
+    }
+
+    // Standard normal distribution, using the Box-Muller transform.
+    pub fn next_gaussian(&mut self) -> f32 {
+        let u1 = self.next_uniform();
+        let u2 = self.next_uniform();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
@@ -0,0 +1,246 @@
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use std::f32::consts::E;
+
+use crate::airplane::Airplane;
+use crate::random::Random;
+use crate::wind::Wind;
+
+pub struct SoaringPlugin;
+
+impl Plugin for SoaringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoaringWeather>()
+            .add_system(update_thermals);
+    }
+}
+
+pub struct Thermal {
+    pub position: Vec2, // horizontal position of the core
+    pub strength: f32,  // m/s, convective velocity scale
+    pub age: f32,       // seconds
+    pub lifetime: f32,  // seconds
+}
+
+impl Thermal {
+    // Development of the thermal over its lifetime: building up, mature, decaying.
+    fn lifecycle_factor(&self) -> f32 {
+        let phase = self.age / self.lifetime;
+        smoothstep(0.0, 0.2, phase) * (1.0 - smoothstep(0.7, 1.0, phase))
+    }
+
+    // Updraft model after Allen (2006): the thermal widens with height and weakens towards
+    // the cloud base. Radially, a core of rising air is surrounded by a ring of sink.
+    pub fn vertical_velocity(&self, position: Vec3, cloud_base: f32) -> f32 {
+        if position.y <= 0.0 || position.y >= cloud_base {
+            return 0.0;
+        }
+        let relative_height = position.y / cloud_base;
+        let radius = (0.102 * relative_height.cbrt() * (1.0 - 0.25 * relative_height) * cloud_base)
+            .max(10.0);
+        let mean_updraft =
+            (self.strength * relative_height.cbrt() * (1.0 - 1.1 * relative_height)).max(0.0);
+
+        // Normalized so that the average over the core (r < radius) equals the mean updraft.
+        let r2 = (position.xz() - self.position).length_squared() / (radius * radius);
+        let peak_updraft = mean_updraft * E;
+        peak_updraft * (-r2).exp() * (1.0 - r2) * self.lifecycle_factor()
+    }
+}
+
+// A ridge of terrain along a line segment with a Gaussian cross section.
+pub struct Ridge {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub height: f32,
+    pub half_width: f32,
+}
+
+impl Ridge {
+    pub fn terrain_height(&self, position: Vec2) -> f32 {
+        let segment = self.end - self.start;
+        let t = ((position - self.start).dot(segment) / segment.length_squared().max(1e-6))
+            .clamp(0.0, 1.0);
+        let distance = position.distance(self.start + segment * t);
+        self.height * (-(distance / self.half_width).powi(2)).exp()
+    }
+
+    // Air following the terrain is deflected upwards where the wind blows against the
+    // slope. The effect fades out above the terrain over about one ridge height.
+    pub fn vertical_velocity(&self, position: Vec3, wind_velocity: Vec3) -> f32 {
+        let step = 1.0;
+        let horizontal_position = position.xz();
+        let terrain_height = self.terrain_height(horizontal_position);
+        let slope = Vec2::new(
+            self.terrain_height(horizontal_position + Vec2::X * step)
+                - self.terrain_height(horizontal_position - Vec2::X * step),
+            self.terrain_height(horizontal_position + Vec2::Y * step)
+                - self.terrain_height(horizontal_position - Vec2::Y * step),
+        ) / (2.0 * step);
+
+        let height_above_terrain = (position.y - terrain_height).max(0.0);
+        wind_velocity.xz().dot(slope) * (-height_above_terrain / self.height.max(1.0)).exp()
+    }
+
+    // Grid mesh of the ridge's terrain, reaching out to three half widths beside the ridge
+    // where the terrain has flattened out. The ridge lift is computed from the same height
+    // function, so the collider built from this mesh matches the lift.
+    pub fn terrain_mesh(&self, spacing: f32) -> Mesh {
+        let margin = Vec2::splat(3.0 * self.half_width);
+        let min = self.start.min(self.end) - margin;
+        let max = self.start.max(self.end) + margin;
+        let columns = ((max.x - min.x) / spacing).ceil() as u32 + 1;
+        let rows = ((max.y - min.y) / spacing).ceil() as u32 + 1;
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let position = min + Vec2::new(column as f32, row as f32) * spacing;
+                let height = |offset: Vec2| self.terrain_height(position + offset);
+                let normal = Vec3::new(
+                    height(-Vec2::X) - height(Vec2::X),
+                    2.0,
+                    height(-Vec2::Y) - height(Vec2::Y),
+                )
+                .normalize();
+                positions.push([position.x, height(Vec2::ZERO), position.y]);
+                normals.push(normal.to_array());
+                uvs.push([
+                    column as f32 / (columns - 1) as f32,
+                    row as f32 / (rows - 1) as f32,
+                ]);
+            }
+        }
+
+        // Two counterclockwise triangles per grid cell, seen from above
+        let mut indices = Vec::new();
+        for row in 0..rows - 1 {
+            for column in 0..columns - 1 {
+                let a = row * columns + column;
+                let b = a + columns;
+                indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+#[derive(Resource)]
+pub struct SoaringWeather {
+    pub cloud_base: f32,           // meters
+    pub convective_velocity: f32,  // m/s
+    pub thermal_count: usize,      // thermals kept alive around the airplane
+    pub thermal_lifetime: f32,     // seconds
+    pub thermal_spawn_radius: f32, // meters
+    pub thermals: Vec<Thermal>,
+    pub ridges: Vec<Ridge>,
+    pub seed: u64,
+    random: Random,
+}
+
+impl Default for SoaringWeather {
+    fn default() -> Self {
+        Self {
+            cloud_base: 1500.0,
+            convective_velocity: 2.5,
+            thermal_count: 12,
+            thermal_lifetime: 900.0,
+            thermal_spawn_radius: 4000.0,
+            thermals: Vec::new(),
+            ridges: Vec::new(),
+            seed: 0,
+            random: Random::new(0),
+        }
+    }
+}
+
+impl SoaringWeather {
+    // Removes the thermals and restarts the random sequence, so that the same thermals are
+    // spawned again around the airplane. The ridges stay.
+    pub fn reset(&mut self) {
+        self.thermals.clear();
+        self.random = Random::new(self.seed);
+    }
+
+    pub fn vertical_velocity(&self, position: Vec3, wind_velocity: Vec3) -> f32 {
+        let thermal_lift: f32 = self
+            .thermals
+            .iter()
+            .map(|thermal| thermal.vertical_velocity(position, self.cloud_base))
+            .sum();
+        let ridge_lift: f32 = self
+            .ridges
+            .iter()
+            .map(|ridge| ridge.vertical_velocity(position, wind_velocity))
+            .sum();
+        thermal_lift + ridge_lift
+    }
+
+    fn spawn_thermal(&mut self, center: Vec2, age_fraction: f32) -> Thermal {
+        let distance = self.thermal_spawn_radius * self.random.next_uniform().sqrt();
+        let angle = self.random.next_range(0.0, std::f32::consts::TAU);
+        let lifetime = self.thermal_lifetime * self.random.next_range(0.5, 1.5);
+        Thermal {
+            position: center + Vec2::new(angle.cos(), angle.sin()) * distance,
+            strength: self.convective_velocity * self.random.next_range(0.6, 1.4),
+            age: lifetime * age_fraction,
+            lifetime,
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn update_thermals(
+    mut weather: ResMut<SoaringWeather>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+    airplane_query: Query<&Transform, With<Airplane>>,
+) {
+    let center = airplane_query
+        .iter()
+        .next()
+        .map_or(Vec2::ZERO, |transform| transform.translation.xz());
+    let delta_seconds = time.delta_seconds();
+    let elapsed_seconds = time.elapsed_seconds();
+    let weather = &mut *weather;
+
+    // Thermals drift with the wind in the middle of the convective layer.
+    let drift_height = weather.cloud_base * 0.5;
+    for thermal in &mut weather.thermals {
+        thermal.age += delta_seconds;
+        let drift_position = Vec3::new(thermal.position.x, drift_height, thermal.position.y);
+        thermal.position +=
+            wind.wind_velocity(drift_position, elapsed_seconds).xz() * delta_seconds;
+    }
+
+    // Replace thermals that have died or were left far behind.
+    let max_distance = weather.thermal_spawn_radius * 1.5;
+    weather.thermals.retain(|thermal| {
+        thermal.age < thermal.lifetime && thermal.position.distance(center) < max_distance
+    });
+
+    // Initially the thermals are spread over their whole lifecycle.
+    let initial = weather.thermals.is_empty();
+    while weather.thermals.len() < weather.thermal_count {
+        let age_fraction = if initial {
+            weather.random.next_uniform()
+        } else {
+            0.0
+        };
+        let thermal = weather.spawn_thermal(center, age_fraction);
+        weather.thermals.push(thermal);
+    }
+}
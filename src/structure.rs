@@ -0,0 +1,690 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::prelude::SharedShape;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::aerodynamics::{simulate_aerodynamics, AeroSurfaceList, ControlInputType};
+use crate::atmosphere::{Atmosphere, ISA_SEA_LEVEL_DENSITY};
+use crate::collider::GltfColliderParts;
+use crate::input::{AirplaneAction, AirplaneControls};
+use crate::landing_gear::LandingGear;
+use crate::mass::{point_mass, MassBalance};
+use crate::model::{visit_hierarchy, ModelAnimation};
+use crate::wind::Wind;
+
+pub struct StructurePlugin;
+
+impl Plugin for StructurePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Structure>()
+            .add_event::<StructuralWarning>()
+            .add_event::<StructuralFailure>()
+            .add_system(reset_g_meter.before(check_structural_limits))
+            .add_system(check_structural_limits.after(simulate_aerodynamics))
+            .add_system(break_off_surfaces.after(check_structural_limits));
+    }
+}
+
+// Control surface deflection, as a fraction of the actuator's range, that counts as full
+// deflection above the manoeuvring speed.
+const FULL_DEFLECTION: f32 = 0.5;
+
+// A surface that breaks off once the ultimate load factor is exceeded.
+#[derive(Reflect, FromReflect, Deserialize, Clone, Debug)]
+pub struct BreakableSurface {
+    pub surface: usize, // index into the airplane's surfaces
+    // Index of the collider file, or of the tagged model node, whose part of the airplane's
+    // compound collider breaks off with the surface.
+    #[serde(default)]
+    pub collider: Option<usize>,
+    // Name of the model node showing the surface, which falls with the debris.
+    #[serde(default)]
+    pub node: Option<String>,
+    pub mass: f32, // kg, of the debris
+}
+
+// Limits of the flight envelope. Speeds are indicated airspeeds.
+#[derive(Reflect, FromReflect, Deserialize, Clone)]
+pub struct StructuralLimits {
+    pub never_exceed_speed: f32, // m/s
+    pub manoeuvring_speed: f32,  // m/s
+    pub max_load_factor: f32,
+    pub min_load_factor: f32,
+    // Multiple of the load factor limits at which the structure fails.
+    #[serde(default = "StructuralLimits::default_ultimate_factor")]
+    pub ultimate_factor: f32,
+    #[serde(default)]
+    pub breakable_surfaces: Vec<BreakableSurface>,
+}
+
+impl StructuralLimits {
+    fn default_ultimate_factor() -> f32 {
+        1.5
+    }
+}
+
+#[derive(Reflect, FromReflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StructuralLimit {
+    NeverExceedSpeed,
+    ManoeuvringSpeed,
+    MaxLoadFactor,
+    MinLoadFactor,
+}
+
+// Sent once when a limit is exceeded, and again after the airplane was back within it.
+#[derive(Clone, Copy, Debug)]
+pub struct StructuralWarning {
+    pub airplane: Entity,
+    pub limit: StructuralLimit,
+    pub value: f32, // m/s or load factor
+}
+
+#[derive(Clone, Debug)]
+pub struct StructuralFailure {
+    pub airplane: Entity,
+    pub surface: BreakableSurface,
+}
+
+// Limits and G-meter of an airplane. The G-meter keeps the extreme load factors until it is
+// reset.
+#[derive(Reflect, Component, Clone)]
+pub struct Structure {
+    pub limits: StructuralLimits,
+    pub g_meter_max: f32,
+    pub g_meter_min: f32,
+    pub exceeded_limits: Vec<StructuralLimit>,
+    pub failed: bool,
+}
+
+impl Structure {
+    pub fn new(limits: StructuralLimits) -> Self {
+        Self {
+            limits,
+            g_meter_max: 1.0,
+            g_meter_min: 1.0,
+            exceeded_limits: Vec::new(),
+            failed: false,
+        }
+    }
+
+    pub fn reset_g_meter(&mut self) {
+        self.g_meter_max = 1.0;
+        self.g_meter_min = 1.0;
+    }
+}
+
+fn reset_g_meter(
+    input_query: Query<&ActionState<AirplaneAction>, With<AirplaneControls>>,
+    mut structure_query: Query<&mut Structure>,
+) {
+    if input_query
+        .single()
+        .just_pressed(AirplaneAction::ResetGMeter)
+    {
+        for mut structure in &mut structure_query {
+            structure.reset_g_meter();
+        }
+    }
+}
+
+pub fn check_structural_limits(
+    mut airplane_query: Query<(
+        Entity,
+        &mut Structure,
+        &AeroSurfaceList,
+        Option<&LandingGear>,
+        &Transform,
+        &Velocity,
+    )>,
+    mut warning_events: EventWriter<StructuralWarning>,
+    mut failure_events: EventWriter<StructuralFailure>,
+    atmosphere: Res<Atmosphere>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    for (entity, mut structure, surface_list, landing_gear, transform, velocity) in
+        &mut airplane_query
+    {
+        let true_air_velocity =
+            velocity.linvel - wind.wind_velocity(transform.translation, time.elapsed_seconds());
+        let indicated_airspeed = true_air_velocity.length()
+            * (atmosphere.density(transform.translation.y) / ISA_SEA_LEVEL_DENSITY).sqrt();
+        let load_factor = surface_list.load_factor;
+        // The load factor only counts aerodynamic forces, on the ground the gear carries the
+        // airplane and the G-meter stops.
+        let on_ground = landing_gear.map_or(false, |landing_gear| {
+            landing_gear
+                .struts
+                .iter()
+                .any(|strut| strut.compression > 0.0)
+        });
+        if !on_ground {
+            structure.g_meter_max = structure.g_meter_max.max(load_factor);
+            structure.g_meter_min = structure.g_meter_min.min(load_factor);
+        }
+
+        let full_deflection = surface_list.surfaces.iter().any(|(surface, _)| {
+            let range = match surface.input_type {
+                ControlInputType::Roll | ControlInputType::Pitch | ControlInputType::Yaw => {
+                    surface.actuator.max_angle.max(-surface.actuator.min_angle)
+                }
+                ControlInputType::None | ControlInputType::Flap => return false,
+            };
+            surface.control_surface_angle.abs() > FULL_DEFLECTION * range
+        });
+
+        let limits = structure.limits.clone();
+        let checks = [
+            (
+                StructuralLimit::NeverExceedSpeed,
+                indicated_airspeed > limits.never_exceed_speed,
+                indicated_airspeed,
+            ),
+            (
+                StructuralLimit::ManoeuvringSpeed,
+                indicated_airspeed > limits.manoeuvring_speed && full_deflection,
+                indicated_airspeed,
+            ),
+            (
+                StructuralLimit::MaxLoadFactor,
+                load_factor > limits.max_load_factor,
+                load_factor,
+            ),
+            (
+                StructuralLimit::MinLoadFactor,
+                load_factor < limits.min_load_factor,
+                load_factor,
+            ),
+        ];
+        for (limit, exceeded, value) in checks {
+            let was_exceeded = structure.exceeded_limits.contains(&limit);
+            if exceeded && !was_exceeded {
+                warn!("Structural limit exceeded: {:?} at {:.1}", limit, value);
+                warning_events.send(StructuralWarning {
+                    airplane: entity,
+                    limit,
+                    value,
+                });
+                structure.exceeded_limits.push(limit);
+            } else if !exceeded && was_exceeded {
+                structure.exceeded_limits.retain(|&other| other != limit);
+            }
+        }
+
+        let ultimate_load_exceeded = load_factor > limits.max_load_factor * limits.ultimate_factor
+            || load_factor < limits.min_load_factor * limits.ultimate_factor;
+        if structure.failed || !ultimate_load_exceeded {
+            continue;
+        }
+        // The most loaded of the breakable surfaces fails first.
+        let failing_surface = limits
+            .breakable_surfaces
+            .iter()
+            .filter_map(|breakable| {
+                surface_list
+                    .surfaces
+                    .get(breakable.surface)
+                    .map(|(surface, _)| (breakable, surface.lift.length()))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(breakable, _)| breakable.clone());
+        if let Some(surface) = failing_surface {
+            warn!(
+                "Structural failure of surface {} at {:.1} g",
+                surface.surface, load_factor
+            );
+            structure.failed = true;
+            failure_events.send(StructuralFailure {
+                airplane: entity,
+                surface,
+            });
+        }
+    }
+}
+
+// A part that broke off an airplane. It is removed when the airplane is reset.
+#[derive(Component, Clone, Copy)]
+pub struct Debris {
+    pub airplane: Entity,
+}
+
+// Detaches failed surfaces from the airplane. The part of the collider belonging to the
+// surface continues as a separate debris body together with the surface's model node, and
+// its mass is taken from the airplane. Without a collider part the model node is hidden.
+fn break_off_surfaces(
+    mut commands: Commands,
+    mut failure_events: EventReader<StructuralFailure>,
+    mut airplane_query: Query<(
+        &mut AeroSurfaceList,
+        Option<&mut ModelAnimation>,
+        Option<&mut MassBalance>,
+        Option<&Collider>,
+        Option<&GltfColliderParts>,
+        &ReadMassProperties,
+        &Transform,
+        &Velocity,
+    )>,
+    hierarchy_query: Query<(&Transform, Option<&Children>)>,
+    name_query: Query<&Name>,
+) {
+    for failure in failure_events.iter() {
+        let (
+            mut surface_list,
+            model_animation,
+            mass_balance,
+            collider,
+            gltf_collider_parts,
+            read_mass_properties,
+            transform,
+            velocity,
+        ) = match airplane_query.get_mut(failure.airplane) {
+            Ok(airplane) => airplane,
+            Err(_) => continue,
+        };
+        let breakable = &failure.surface;
+        if breakable.surface >= surface_list.surfaces.len() {
+            continue;
+        }
+        let (detached_surface, surface_transform) = surface_list.detach_surface(breakable.surface);
+        if let Some(mut model_animation) = model_animation {
+            model_animation.detach_surface(&detached_surface.name);
+        }
+
+        // The node's transform relative to the airplane, which is also its transform relative
+        // to the debris.
+        let mut model_node = None;
+        if let Some(node_name) = &breakable.node {
+            visit_hierarchy(
+                failure.airplane,
+                Transform::IDENTITY,
+                &hierarchy_query,
+                &mut |node, node_transform| {
+                    if model_node.is_none()
+                        && name_query
+                            .get(node)
+                            .map_or(false, |name| name.as_str() == node_name)
+                    {
+                        model_node = Some((node, node_transform));
+                    }
+                },
+            );
+            if model_node.is_none() {
+                warn!(
+                    "Breakable surface: the model has no node named \"{}\"",
+                    node_name
+                );
+            }
+        }
+
+        // Collider files give one part each, tagged model nodes one or more. The airplane
+        // needs at least one part of its collider to remain.
+        let compound = collider.and_then(|collider| collider.raw.as_compound());
+        let parts = breakable
+            .collider
+            .and_then(|index| match gltf_collider_parts {
+                Some(gltf_collider_parts) => gltf_collider_parts.node_parts.get(index).cloned(),
+                None => Some(index..index + 1),
+            });
+        let (parts, compound) = match (parts, compound) {
+            (Some(parts), Some(compound))
+                if !parts.is_empty()
+                    && parts.end <= compound.shapes().len()
+                    && parts.len() < compound.shapes().len() =>
+            {
+                (parts, compound)
+            }
+            _ => {
+                if let Some((node, _)) = model_node {
+                    commands.entity(node).insert(Visibility::INVISIBLE);
+                }
+                continue;
+            }
+        };
+        let mut remaining_parts = compound.shapes().to_vec();
+        let debris_parts = remaining_parts.drain(parts).collect::<Vec<_>>();
+
+        // The debris' mass is assumed to be centered on its surface.
+        let debris_position = surface_transform.translation;
+        if let Some(mut mass_balance) = mass_balance {
+            mass_balance.empty_mass = MassProperties::from_rapier(
+                mass_balance.empty_mass.into_rapier(1.0)
+                    - point_mass(debris_position, breakable.mass),
+                1.0,
+            );
+        }
+        let world_center_of_mass =
+            transform.transform_point(read_mass_properties.0.local_center_of_mass);
+        let relative_position = transform.transform_point(debris_position) - world_center_of_mass;
+
+        commands
+            .entity(failure.airplane)
+            .insert(Collider::from(SharedShape::compound(remaining_parts)));
+        let debris = commands
+            .spawn((
+                Name::new("Debris"),
+                Debris {
+                    airplane: failure.airplane,
+                },
+                RigidBody::Dynamic,
+                Collider::from(SharedShape::compound(debris_parts)),
+                ColliderMassProperties::Mass(breakable.mass),
+                TransformBundle::from_transform(*transform),
+                VisibilityBundle::default(),
+                Velocity {
+                    linvel: velocity.linvel + velocity.angvel.cross(relative_position),
+                    angvel: velocity.angvel,
+                },
+            ))
+            .id();
+        if let Some((node, node_transform)) = model_node {
+            commands.entity(node).insert(node_transform);
+            commands.entity(debris).add_child(node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aerodynamics::AeroSurface;
+    use bevy::utils::{Duration, Instant};
+
+    fn wing(name: &str, lift: f32) -> (AeroSurface, Transform) {
+        let surface = AeroSurface {
+            name: name.to_string(),
+            lift: Vec3::new(0.0, lift, 0.0),
+            ..default()
+        };
+        (surface, Transform::IDENTITY)
+    }
+
+    fn elevator(angle: f32) -> (AeroSurface, Transform) {
+        let surface = AeroSurface {
+            name: "Elevator".to_string(),
+            input_type: ControlInputType::Pitch,
+            control_surface_angle: angle,
+            ..default()
+        };
+        (surface, Transform::IDENTITY)
+    }
+
+    fn breakable(surface: usize) -> BreakableSurface {
+        BreakableSurface {
+            surface,
+            collider: None,
+            node: None,
+            mass: 95.0,
+        }
+    }
+
+    // The limits of the ASK 21.
+    fn limits() -> StructuralLimits {
+        StructuralLimits {
+            never_exceed_speed: 77.8,
+            manoeuvring_speed: 50.0,
+            max_load_factor: 5.3,
+            min_load_factor: -2.65,
+            ultimate_factor: 1.5,
+            breakable_surfaces: vec![breakable(0), breakable(1)],
+        }
+    }
+
+    fn spawn_airplane(world: &mut World, surfaces: Vec<(AeroSurface, Transform)>) -> Entity {
+        world.init_resource::<Events<StructuralWarning>>();
+        world.init_resource::<Events<StructuralFailure>>();
+        world.insert_resource(Atmosphere::default());
+        world.insert_resource(Wind::default());
+        let start = Instant::now();
+        let mut time = Time::default();
+        time.update_with_instant(start);
+        time.update_with_instant(start + Duration::from_secs_f32(0.02));
+        world.insert_resource(time);
+        world
+            .spawn((
+                Structure::new(limits()),
+                AeroSurfaceList {
+                    surfaces,
+                    ..default()
+                },
+                Transform::IDENTITY,
+                Velocity::zero(),
+            ))
+            .id()
+    }
+
+    // Flies at an indicated airspeed (at sea level) and load factor, with the elevator at an
+    // angle, and returns the limits warned about.
+    fn fly(
+        world: &mut World,
+        airplane: Entity,
+        airspeed: f32,
+        load_factor: f32,
+        elevator_angle: f32,
+    ) -> Vec<StructuralLimit> {
+        let mut entity = world.entity_mut(airplane);
+        entity.get_mut::<Velocity>().unwrap().linvel = Vec3::new(0.0, 0.0, -airspeed);
+        let mut surface_list = entity.get_mut::<AeroSurfaceList>().unwrap();
+        surface_list.load_factor = load_factor;
+        for (surface, _) in &mut surface_list.surfaces {
+            if surface.name == "Elevator" {
+                surface.control_surface_angle = elevator_angle;
+            }
+        }
+        world.resource_mut::<Events<StructuralWarning>>().update();
+        world.resource_mut::<Events<StructuralFailure>>().update();
+
+        SystemStage::single(check_structural_limits).run(world);
+
+        world
+            .resource::<Events<StructuralWarning>>()
+            .iter_current_update_events()
+            .map(|warning| warning.limit)
+            .collect()
+    }
+
+    fn failures(world: &World) -> Vec<usize> {
+        world
+            .resource::<Events<StructuralFailure>>()
+            .iter_current_update_events()
+            .map(|failure| failure.surface.surface)
+            .collect()
+    }
+
+    #[test]
+    fn each_limit_is_warned_about() {
+        let mut world = World::new();
+        let airplane = spawn_airplane(&mut world, vec![elevator(0.0)]);
+
+        assert!(fly(&mut world, airplane, 40.0, 1.0, 0.0).is_empty());
+        assert_eq!(
+            fly(&mut world, airplane, 80.0, 1.0, 0.0),
+            vec![StructuralLimit::NeverExceedSpeed]
+        );
+        // Above the manoeuvring speed only more than half of the elevator's 45 degrees count.
+        assert!(fly(&mut world, airplane, 60.0, 1.0, 0.3).is_empty());
+        assert_eq!(
+            fly(&mut world, airplane, 60.0, 1.0, -0.5),
+            vec![StructuralLimit::ManoeuvringSpeed]
+        );
+        assert!(fly(&mut world, airplane, 40.0, 1.0, 0.5).is_empty());
+        assert_eq!(
+            fly(&mut world, airplane, 40.0, 5.5, 0.0),
+            vec![StructuralLimit::MaxLoadFactor]
+        );
+        assert_eq!(
+            fly(&mut world, airplane, 40.0, -3.0, 0.0),
+            vec![StructuralLimit::MinLoadFactor]
+        );
+
+        let structure = world.get::<Structure>(airplane).unwrap();
+        assert_eq!(structure.g_meter_max, 5.5);
+        assert_eq!(structure.g_meter_min, -3.0);
+        // Within the ultimate loads nothing breaks.
+        assert!(!structure.failed);
+    }
+
+    #[test]
+    fn warnings_repeat_only_after_returning_within_the_limit() {
+        let mut world = World::new();
+        let airplane = spawn_airplane(&mut world, vec![]);
+
+        assert_eq!(
+            fly(&mut world, airplane, 40.0, 6.0, 0.0),
+            vec![StructuralLimit::MaxLoadFactor]
+        );
+        assert!(fly(&mut world, airplane, 40.0, 6.5, 0.0).is_empty());
+        assert!(fly(&mut world, airplane, 40.0, 5.5, 0.0).is_empty());
+        assert_eq!(
+            world.get::<Structure>(airplane).unwrap().exceeded_limits,
+            vec![StructuralLimit::MaxLoadFactor]
+        );
+
+        assert!(fly(&mut world, airplane, 40.0, 5.0, 0.0).is_empty());
+        assert!(world
+            .get::<Structure>(airplane)
+            .unwrap()
+            .exceeded_limits
+            .is_empty());
+        assert_eq!(
+            fly(&mut world, airplane, 40.0, 6.0, 0.0),
+            vec![StructuralLimit::MaxLoadFactor]
+        );
+
+        // The G-meter keeps the peak.
+        assert_eq!(world.get::<Structure>(airplane).unwrap().g_meter_max, 6.5);
+    }
+
+    #[test]
+    fn the_most_loaded_surface_fails_beyond_the_ultimate_load() {
+        let mut world = World::new();
+        let airplane = spawn_airplane(
+            &mut world,
+            vec![wing("Left wing", 20_000.0), wing("Right wing", 25_000.0)],
+        );
+
+        // 7.5 g is above the limit of 5.3 g, but below the ultimate load of 7.95 g.
+        fly(&mut world, airplane, 40.0, 7.5, 0.0);
+        assert!(failures(&world).is_empty());
+
+        fly(&mut world, airplane, 40.0, 8.0, 0.0);
+        assert_eq!(failures(&world), vec![1]);
+        assert!(world.get::<Structure>(airplane).unwrap().failed);
+
+        // Only the first failure is reported.
+        fly(&mut world, airplane, 40.0, 9.0, 0.0);
+        assert!(failures(&world).is_empty());
+
+        // Negative loads break the structure as well, beyond -3.975 g.
+        let mut world = World::new();
+        let airplane = spawn_airplane(
+            &mut world,
+            vec![wing("Left wing", -12_000.0), wing("Right wing", -10_000.0)],
+        );
+        fly(&mut world, airplane, 40.0, -3.9, 0.0);
+        assert!(failures(&world).is_empty());
+        fly(&mut world, airplane, 40.0, -4.0, 0.0);
+        assert_eq!(failures(&world), vec![0]);
+    }
+
+    #[test]
+    fn broken_surfaces_take_their_collider_part_and_model_node() {
+        let mut world = World::new();
+        world.init_resource::<Events<StructuralFailure>>();
+        let transform = Transform::from_xyz(0.0, 100.0, 0.0);
+        let airplane = world
+            .spawn((
+                AeroSurfaceList {
+                    surfaces: vec![wing("Left wing", 0.0), wing("Right wing", 0.0)],
+                    ..default()
+                },
+                Collider::compound(vec![
+                    (Vec3::ZERO, Quat::IDENTITY, Collider::cuboid(0.5, 0.5, 3.0)),
+                    (
+                        Vec3::new(-4.0, 0.0, 0.0),
+                        Quat::IDENTITY,
+                        Collider::cuboid(4.0, 0.1, 0.5),
+                    ),
+                    (
+                        Vec3::new(4.0, 0.0, 0.0),
+                        Quat::IDENTITY,
+                        Collider::cuboid(4.0, 0.1, 0.5),
+                    ),
+                ]),
+                ReadMassProperties(MassProperties {
+                    mass: 500.0,
+                    ..default()
+                }),
+                transform,
+                Velocity::linear(Vec3::new(0.0, 0.0, -30.0)),
+            ))
+            .id();
+        // The model's nodes below the scene's root.
+        let left_wing = world
+            .spawn((Name::new("Left wing"), Transform::from_xyz(-4.0, 0.0, 0.0)))
+            .id();
+        let right_wing = world
+            .spawn((Name::new("Right wing"), Transform::from_xyz(4.0, 0.0, 0.0)))
+            .id();
+        let scene = world
+            .spawn(Transform::from_xyz(0.0, 1.0, 0.0))
+            .push_children(&[left_wing, right_wing])
+            .id();
+        world.entity_mut(airplane).push_children(&[scene]);
+
+        world.send_event(StructuralFailure {
+            airplane,
+            surface: BreakableSurface {
+                collider: Some(1),
+                node: Some("Left wing".to_string()),
+                ..breakable(0)
+            },
+        });
+        let mut stage = SystemStage::single(break_off_surfaces);
+        stage.run(&mut world);
+
+        let surface_list = world.get::<AeroSurfaceList>(airplane).unwrap();
+        assert_eq!(surface_list.surfaces.len(), 1);
+        assert_eq!(surface_list.surfaces[0].0.name, "Right wing");
+        let collider = world.get::<Collider>(airplane).unwrap();
+        assert_eq!(collider.raw.as_compound().unwrap().shapes().len(), 2);
+
+        let mut debris_query = world.query::<(Entity, &Debris, &Collider, &Transform)>();
+        let (debris, debris_airplane, debris_collider, debris_transform) =
+            debris_query.single(&world);
+        assert_eq!(debris_airplane.airplane, airplane);
+        assert_eq!(*debris_transform, transform);
+        let debris_parts = debris_collider.raw.as_compound().unwrap().shapes();
+        assert_eq!(debris_parts.len(), 1);
+        let part_position: Vec3 = debris_parts[0].0.translation.vector.into();
+        assert_eq!(part_position, Vec3::new(-4.0, 0.0, 0.0));
+
+        // The wing's node moved from the airplane's model to the debris, at the same place.
+        assert_eq!(world.get::<Parent>(left_wing).unwrap().get(), debris);
+        assert_eq!(
+            world.get::<Transform>(left_wing).unwrap().translation,
+            Vec3::new(-4.0, 1.0, 0.0)
+        );
+
+        // Without a collider part the surface has no body to fall with, its node is hidden.
+        world.send_event(StructuralFailure {
+            airplane,
+            surface: BreakableSurface {
+                node: Some("Right wing".to_string()),
+                ..breakable(0)
+            },
+        });
+        stage.run(&mut world);
+
+        assert!(world
+            .get::<AeroSurfaceList>(airplane)
+            .unwrap()
+            .surfaces
+            .is_empty());
+        assert_eq!(
+            *world.get::<Visibility>(right_wing).unwrap(),
+            Visibility::INVISIBLE
+        );
+        assert_eq!(world.get::<Parent>(right_wing).unwrap().get(), scene);
+    }
+}
@@ -0,0 +1,317 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+use crate::aerodynamics::simulate_aerodynamics;
+use crate::random::Random;
+use crate::wind::Wind;
+
+pub struct TurbulencePlugin;
+
+impl Plugin for TurbulencePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Turbulence>()
+            .add_system(update_turbulence.before(simulate_aerodynamics));
+    }
+}
+
+const FEET: f32 = 0.3048; // meters per foot
+
+// Altitudes (ft) of the medium/high altitude turbulence intensity table of MIL-HDBK-1797.
+const HIGH_ALTITUDE_TABLE_ALTITUDES: [f32; 12] = [
+    500.0, 1750.0, 3750.0, 7500.0, 15000.0, 25000.0, 35000.0, 45000.0, 55000.0, 65000.0, 75000.0,
+    80000.0,
+];
+
+#[derive(Reflect, FromReflect, Deserialize, Default, Clone, Copy)]
+pub enum TurbulenceSeverity {
+    #[default]
+    Light,
+    Moderate,
+    Severe,
+}
+
+impl TurbulenceSeverity {
+    // Wind speed at 20 ft (m/s) used by the low altitude model.
+    fn wind_speed_at_20_feet(&self) -> f32 {
+        match self {
+            TurbulenceSeverity::Light => 15.0 * 0.514444,
+            TurbulenceSeverity::Moderate => 30.0 * 0.514444,
+            TurbulenceSeverity::Severe => 45.0 * 0.514444,
+        }
+    }
+
+    // RMS turbulence intensity (ft/s) for the probabilities of exceedance 10⁻², 10⁻³ and 10⁻⁵.
+    fn high_altitude_intensities(&self) -> [f32; 12] {
+        match self {
+            TurbulenceSeverity::Light => {
+                [6.6, 6.9, 7.4, 6.7, 4.6, 2.7, 0.4, 0.0, 0.0, 0.0, 0.0, 0.0]
+            }
+            TurbulenceSeverity::Moderate => {
+                [8.6, 9.6, 10.6, 10.1, 8.0, 6.6, 5.0, 4.2, 2.7, 0.0, 0.0, 0.0]
+            }
+            TurbulenceSeverity::Severe => [
+                15.6, 17.6, 23.0, 23.6, 22.1, 20.0, 16.0, 15.1, 12.1, 7.9, 6.2, 5.1,
+            ],
+        }
+    }
+}
+
+// Turbulence for the airplanes, enabled by an entry in the wind file, e.g.
+// `Turbulence((severity: Moderate, seed: 7))`.
+#[derive(Deserialize, Clone, Copy)]
+pub struct TurbulenceConfig {
+    #[serde(default)]
+    pub severity: TurbulenceSeverity,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+// Scale lengths (m) and RMS intensities (m/s) of the longitudinal, lateral and vertical
+// turbulence components.
+struct TurbulenceScales {
+    lengths: Vec3,
+    intensities: Vec3,
+}
+
+#[derive(Default, Clone, Copy)]
+struct DrydenState {
+    longitudinal: f32,
+    lateral: [f32; 2],
+    vertical: [f32; 2],
+    roll_rate: f32,
+    pitch_rate: f32,
+    yaw_rate: f32,
+    last_lateral: f32,
+    last_vertical: f32,
+    random: Random,
+}
+
+// Continuous turbulence following the MIL-F-8785C Dryden spectra. The gust field is
+// expressed in the airplane's local frame as a linear velocity plus an angular velocity of
+// the air, so that surfaces at different positions see correlated but not identical gusts.
+#[derive(Reflect, Component)]
+pub struct Turbulence {
+    pub severity: TurbulenceSeverity,
+    pub wingspan: f32,
+    pub seed: u64,
+    pub linear_velocity: Vec3,
+    pub angular_velocity: Vec3,
+    #[reflect(ignore)]
+    state: DrydenState,
+}
+
+impl Turbulence {
+    pub fn new(severity: TurbulenceSeverity, wingspan: f32, seed: u64) -> Self {
+        Self {
+            severity,
+            wingspan,
+            seed,
+            linear_velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            state: DrydenState {
+                random: Random::new(seed),
+                ..default()
+            },
+        }
+    }
+
+    pub fn from_config(config: TurbulenceConfig, wingspan: f32) -> Self {
+        Self::new(config.severity, wingspan, config.seed)
+    }
+
+    // The same turbulence from its start, e.g. to repeat a flight.
+    pub fn reset(&self) -> Self {
+        Self::new(self.severity, self.wingspan, self.seed)
+    }
+
+    // Air velocity caused by turbulence at a position relative to the center of mass, both
+    // in the airplane's local frame.
+    pub fn gust_velocity(&self, local_position: Vec3) -> Vec3 {
+        self.linear_velocity + self.angular_velocity.cross(local_position)
+    }
+
+    fn scales(&self, height: f32) -> TurbulenceScales {
+        let low_altitude_limit = 1000.0;
+        let high_altitude_limit = 2000.0;
+
+        let height = (height / FEET).max(10.0);
+        if height <= low_altitude_limit {
+            self.low_altitude_scales(height)
+        } else if height >= high_altitude_limit {
+            self.high_altitude_scales(height)
+        } else {
+            let low = self.low_altitude_scales(low_altitude_limit);
+            let high = self.high_altitude_scales(high_altitude_limit);
+            let t = (height - low_altitude_limit) / (high_altitude_limit - low_altitude_limit);
+            TurbulenceScales {
+                lengths: low.lengths.lerp(high.lengths, t),
+                intensities: low.intensities.lerp(high.intensities, t),
+            }
+        }
+    }
+
+    fn low_altitude_scales(&self, height: f32) -> TurbulenceScales {
+        let factor = 0.177 + 0.000823 * height;
+        let horizontal_length = height / factor.powf(1.2);
+        let vertical_intensity = 0.1 * self.severity.wind_speed_at_20_feet();
+        let horizontal_intensity = vertical_intensity / factor.powf(0.4);
+        TurbulenceScales {
+            lengths: Vec3::new(horizontal_length, horizontal_length, height) * FEET,
+            intensities: Vec3::new(
+                horizontal_intensity,
+                horizontal_intensity,
+                vertical_intensity,
+            ),
+        }
+    }
+
+    fn high_altitude_scales(&self, height: f32) -> TurbulenceScales {
+        let intensities = self.severity.high_altitude_intensities();
+        let intensity = interpolate(&HIGH_ALTITUDE_TABLE_ALTITUDES, &intensities, height) * FEET;
+        TurbulenceScales {
+            lengths: Vec3::splat(1750.0 * FEET),
+            intensities: Vec3::splat(intensity),
+        }
+    }
+
+    fn step(&mut self, height: f32, airspeed: f32, delta_seconds: f32) {
+        if delta_seconds <= 0.0 {
+            return;
+        }
+        let airspeed = airspeed.max(1.0);
+        let scales = self.scales(height);
+        let state = &mut self.state;
+
+        // Longitudinal component: first-order shaping filter, discretized exactly.
+        let tau_u = scales.lengths.x / airspeed;
+        let decay_u = (-delta_seconds / tau_u).exp();
+        state.longitudinal = decay_u * state.longitudinal
+            + scales.intensities.x
+                * (1.0 - decay_u * decay_u).sqrt()
+                * state.random.next_gaussian();
+
+        // Lateral and vertical components: second-order shaping filters
+        // σ·√(τ)·(1 + √3·τ·s) / (1 + τ·s)² driven by unit white noise.
+        let lateral_noise = state.random.next_gaussian();
+        let lateral = second_order_dryden(
+            &mut state.lateral,
+            scales.lengths.y / airspeed,
+            scales.intensities.y,
+            delta_seconds,
+            lateral_noise,
+        );
+        let vertical_noise = state.random.next_gaussian();
+        let vertical = second_order_dryden(
+            &mut state.vertical,
+            scales.lengths.z / airspeed,
+            scales.intensities.z,
+            delta_seconds,
+            vertical_noise,
+        );
+
+        // Rotational components, depending on the wingspan. The roll gust represents the
+        // spanwise variation of the vertical gust, pitch and yaw gusts the variation along
+        // the fuselage as the airplane moves through the frozen gust field.
+        let tau_p = 4.0 * self.wingspan / (PI * airspeed);
+        let decay_p = (-delta_seconds / tau_p).exp();
+        let roll_gain = scales.intensities.z
+            * (0.8 / airspeed).sqrt()
+            * (PI / (4.0 * self.wingspan)).powf(1.0 / 6.0)
+            / scales.lengths.z.powf(1.0 / 3.0);
+        state.roll_rate = decay_p * state.roll_rate
+            + roll_gain
+                * (PI * (1.0 - decay_p * decay_p) / (2.0 * tau_p)).sqrt()
+                * state.random.next_gaussian();
+
+        let tau_q = 4.0 * self.wingspan / (PI * airspeed);
+        let vertical_rate = (vertical - state.last_vertical) / delta_seconds;
+        state.pitch_rate +=
+            (vertical_rate / airspeed - state.pitch_rate) * (1.0 - (-delta_seconds / tau_q).exp());
+
+        let tau_r = 3.0 * self.wingspan / (PI * airspeed);
+        let lateral_rate = (lateral - state.last_lateral) / delta_seconds;
+        state.yaw_rate +=
+            (-lateral_rate / airspeed - state.yaw_rate) * (1.0 - (-delta_seconds / tau_r).exp());
+
+        state.last_lateral = lateral;
+        state.last_vertical = vertical;
+
+        // Local frame: x right, y up, z back.
+        self.linear_velocity = Vec3::new(lateral, vertical, -state.longitudinal);
+        self.angular_velocity = Vec3::new(state.pitch_rate, state.yaw_rate, state.roll_rate);
+    }
+}
+
+fn second_order_dryden(
+    filter: &mut [f32; 2],
+    tau: f32,
+    intensity: f32,
+    delta_seconds: f32,
+    noise: f32,
+) -> f32 {
+    let decay = (-delta_seconds / tau).exp();
+    // Unit variance input to the first stage, scaled to white noise of unit spectral density.
+    let input = noise / delta_seconds.sqrt();
+    filter[0] = decay * filter[0] + (1.0 - decay) * input;
+    filter[1] = decay * filter[1] + (1.0 - decay) * filter[0];
+    intensity * tau.sqrt() * ((1.0 - 3.0f32.sqrt()) * filter[1] + 3.0f32.sqrt() * filter[0])
+}
+
+fn interpolate(xs: &[f32], ys: &[f32], x: f32) -> f32 {
+    if x <= xs[0] {
+        return ys[0];
+    }
+    for i in 1..xs.len() {
+        if x <= xs[i] {
+            let t = (x - xs[i - 1]) / (xs[i] - xs[i - 1]);
+            return ys[i - 1] + t * (ys[i] - ys[i - 1]);
+        }
+    }
+    ys[ys.len() - 1]
+}
+
+fn update_turbulence(
+    mut airplane_query: Query<(&mut Turbulence, &Transform, &Velocity)>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    for (mut turbulence, transform, velocity) in &mut airplane_query {
+        let true_air_velocity =
+            velocity.linvel - wind.wind_velocity(transform.translation, time.elapsed_seconds());
+        turbulence.step(
+            transform.translation.y,
+            true_air_velocity.length(),
+            time.delta_seconds(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gusts(turbulence: &mut Turbulence, steps: usize) -> Vec<(Vec3, Vec3)> {
+        (0..steps)
+            .map(|_| {
+                turbulence.step(300.0, 30.0, 0.02);
+                (turbulence.linear_velocity, turbulence.angular_velocity)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_gusts() {
+        let mut first = Turbulence::new(TurbulenceSeverity::Moderate, 17.0, 42);
+        let mut second = Turbulence::new(TurbulenceSeverity::Moderate, 17.0, 42);
+        let gusts_first = gusts(&mut first, 500);
+        assert_eq!(gusts_first, gusts(&mut second, 500));
+        assert!(gusts_first.iter().any(|(linear, _)| linear.length() > 0.1));
+
+        // A reset starts the sequence again, another seed gives different gusts.
+        assert_eq!(gusts_first, gusts(&mut first.reset(), 500));
+        let mut other = Turbulence::new(TurbulenceSeverity::Moderate, 17.0, 43);
+        assert_ne!(gusts_first, gusts(&mut other, 500));
+    }
+}
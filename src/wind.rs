@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+use crate::turbulence::TurbulenceConfig;
+
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>();
+    }
+}
+
+// Velocity of the air mass itself (the direction the air is moving to), in world space.
+pub trait WindField: Send + Sync + 'static {
+    fn wind_velocity(&self, position: Vec3, time: f32) -> Vec3;
+}
+
+#[derive(Resource, Default)]
+pub struct Wind {
+    pub fields: Vec<Box<dyn WindField>>,
+    // Continuous turbulence of the airplanes, none unless the wind file asks for it.
+    pub turbulence: Option<TurbulenceConfig>,
+}
+
+impl Wind {
+    pub fn with(mut self, field: impl WindField) -> Self {
+        self.fields.push(Box::new(field));
+        self
+    }
+
+    // Reads a list of wind fields from a RON file, e.g. `assets/wind/demo.ron`.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let configs: Vec<WindFieldConfig> =
+            ron::from_str(&text).map_err(|error| error.to_string())?;
+        Ok(configs
+            .into_iter()
+            .fold(Wind::default(), |wind, config| match config {
+                WindFieldConfig::Constant(field) => wind.with(field),
+                WindFieldConfig::Shear(field) => wind.with(field),
+                WindFieldConfig::Gust(field) => wind.with(field),
+                WindFieldConfig::Turbulence(turbulence) => Wind {
+                    turbulence: Some(turbulence),
+                    ..wind
+                },
+            }))
+    }
+
+    pub fn wind_velocity(&self, position: Vec3, time: f32) -> Vec3 {
+        self.fields
+            .iter()
+            .map(|field| field.wind_velocity(position, time))
+            .sum()
+    }
+}
+
+#[derive(Deserialize)]
+pub enum WindFieldConfig {
+    Constant(ConstantWind),
+    Shear(WindShear),
+    Gust(Gust),
+    Turbulence(TurbulenceConfig),
+}
+
+#[derive(Deserialize)]
+pub struct ConstantWind {
+    pub velocity: Vec3,
+}
+
+impl WindField for ConstantWind {
+    fn wind_velocity(&self, _position: Vec3, _time: f32) -> Vec3 {
+        self.velocity
+    }
+}
+
+// Logarithmic wind profile of the atmospheric boundary layer. The wind speed is zero at the
+// roughness length, equals `reference_velocity` at `reference_height` and stays constant
+// above `boundary_layer_height`.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct WindShear {
+    pub reference_velocity: Vec3,
+    pub reference_height: f32,
+    pub roughness_length: f32,
+    pub boundary_layer_height: f32,
+}
+
+impl Default for WindShear {
+    fn default() -> Self {
+        Self {
+            reference_velocity: Vec3::ZERO,
+            reference_height: 10.0,       // meters, standard anemometer height
+            roughness_length: 0.03,       // meters, open flat terrain
+            boundary_layer_height: 500.0, // meters
+        }
+    }
+}
+
+impl WindField for WindShear {
+    fn wind_velocity(&self, position: Vec3, _time: f32) -> Vec3 {
+        let height = position.y.min(self.boundary_layer_height);
+        if height <= self.roughness_length {
+            return Vec3::ZERO;
+        }
+        let factor = (height / self.roughness_length).ln()
+            / (self.reference_height / self.roughness_length).ln();
+        self.reference_velocity * factor
+    }
+}
+
+// Discrete "1 - cosine" gust. The gust velocity rises from zero to `amplitude` and back to
+// zero over `duration` seconds, starting at `start_time`.
+#[derive(Deserialize)]
+pub struct Gust {
+    pub amplitude: Vec3,
+    pub start_time: f32,
+    pub duration: f32,
+}
+
+impl WindField for Gust {
+    fn wind_velocity(&self, _position: Vec3, time: f32) -> Vec3 {
+        let t = time - self.start_time;
+        if t < 0.0 || t > self.duration {
+            return Vec3::ZERO;
+        }
+        self.amplitude * 0.5 * (1.0 - (2.0 * PI * t / self.duration).cos())
+    }
+}